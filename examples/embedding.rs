@@ -0,0 +1,50 @@
+//! Runs a script from a Rust program, exposing a native to it
+//! and calling one of its functions back, within limits
+use std::time::Duration;
+use yayarlox::limits::Limits;
+use yayarlox::{errors, lex_and_parse, types, Interpreter, Interrupt};
+
+const SCRIPT: &str = r#"
+var greeting = "Hello";
+
+fun greet(name) {
+    print greeting + ", " + name + "! Twice " + str(limit) + " is " + str(double(limit)) + ".";
+}
+"#;
+
+fn main() {
+    // no files, environment, clock or randomness for the script
+    let mut interpreter = Interpreter::builder().build();
+
+    // the script isn't trusted to end by itself
+    interpreter.limits = Limits {
+        steps: Some(100_000),
+        time: Some(Duration::from_secs(1)),
+        ..Limits::default()
+    };
+
+    interpreter.define_native("double", 1, |arguments| match arguments[0] {
+        types::number(value) => Ok(types::number(value * 2.0)),
+        _ => Err(String::from("Argument must be a number.")),
+    });
+    interpreter.set_global("limit", types::number(21.0));
+
+    let program = match lex_and_parse("<embedded>", String::from(SCRIPT)) {
+        Ok(program) => program,
+        Err(summary) => {
+            errors::summary(summary);
+            return;
+        }
+    };
+
+    let result = interpreter.interpret(program).and_then(|_| {
+        let greet = interpreter.get_global("greet").unwrap_or(types::nil);
+        interpreter.call(greet, vec![types::string(String::from("host"))])
+    });
+
+    if let Err(Interrupt::Error(error)) = result {
+        error.report();
+    }
+
+    println!("greeting = {:?}", interpreter.get_global("greeting"));
+}
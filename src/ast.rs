@@ -1,5 +1,5 @@
 /// Generate public Expressions and Statements
-use crate::lexer::Token;
+use crate::lexer::{Span, Token};
 
 macro_rules! define_ast {
     ( $Category:ident :=
@@ -22,6 +22,7 @@ define_ast!(
         Assign   : Token name, Expr value ;
         Binary   : Expr left, Token operator, Expr right ;
         Call     : Expr callee, Token paren, Vec<Expr> arguments ;
+        Get      : Expr object, Token name ;
         Grouping : Expr expr ;
         Literal  : Token value ;
         Logical  : Expr left, Token operator, Expr right ;
@@ -32,10 +33,123 @@ define_ast!(
 define_ast!(
     Stmt :=
         Block      : Vec<Stmt> statements ;
+        Debugger   : Token keyword ;
         Expression : Expr expression ;
         Function   : Token name, Vec<Token> params, Vec<Stmt> body ;
         If         : Expr condition, Stmt then_branch, Stmt else_branch ;
+        Import     : Token keyword, Token path, Option<Token> name ;
         Print      : Expr expression ;
+        Test       : Token keyword, Token name, Vec<Stmt> body ;
+        Throw      : Token keyword, Expr value ;
+        Try        : Token keyword, Vec<Stmt> body, Option<Token> name, Vec<Stmt> handler, Vec<Stmt> finally ;
         Var        : Token name, Expr initializer ;
         While      : Expr condition, Stmt body
 );
+
+impl Expr {
+    /// Line where the expression starts
+    pub fn line(&self) -> usize {
+        match self {
+            Expr::Assign(name, _) => name.line,
+            Expr::Binary(left, _, _) | Expr::Logical(left, _, _) => left.line(),
+            Expr::Call(callee, _, _) | Expr::Get(callee, _) => callee.line(),
+            Expr::Grouping(expr) => expr.line(),
+            Expr::Literal(token) | Expr::Variable(token) => token.line,
+            Expr::Unary(operator, _) => operator.line,
+        }
+    }
+
+    /// Code covered by the tokens of the expression,
+    /// the parentheses of groupings excluded
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Assign(name, value) => name.span.to(value.span()),
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                left.span().to(right.span())
+            }
+            Expr::Call(callee, paren, _) => callee.span().to(paren.span),
+            Expr::Get(object, name) => object.span().to(name.span),
+            Expr::Grouping(expr) => expr.span(),
+            Expr::Literal(token) | Expr::Variable(token) => token.span,
+            Expr::Unary(operator, right) => operator.span.to(right.span()),
+        }
+    }
+}
+
+impl Stmt {
+    /// Whether the statement declares a function, at any depth
+    pub fn declares_function(&self) -> bool {
+        match self {
+            Stmt::Function(_, _, _) => true,
+            Stmt::Block(statements) | Stmt::Test(_, _, statements) => {
+                statements.iter().any(Stmt::declares_function)
+            }
+            Stmt::If(_, then_branch, else_branch) => {
+                then_branch.declares_function() || else_branch.declares_function()
+            }
+            Stmt::Try(_, body, _, handler, finally) => [body, handler, finally]
+                .iter()
+                .flat_map(|statements| statements.iter())
+                .any(Stmt::declares_function),
+            Stmt::While(_, body) => body.declares_function(),
+            _ => false,
+        }
+    }
+
+    /// Line where the statement starts, None for empty blocks
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Stmt::Block(statements) => statements.iter().find_map(Stmt::line),
+            Stmt::Debugger(keyword)
+            | Stmt::Import(keyword, _, _)
+            | Stmt::Test(keyword, _, _)
+            | Stmt::Throw(keyword, _)
+            | Stmt::Try(keyword, _, _, _, _) => Some(keyword.line),
+            Stmt::Expression(expr) | Stmt::Print(expr) => Some(expr.line()),
+            Stmt::Function(name, _, _) | Stmt::Var(name, _) => Some(name.line),
+            Stmt::If(condition, _, _) | Stmt::While(condition, _) => Some(condition.line()),
+        }
+    }
+
+    /// Code covered by the tokens of the statement, keywords aside
+    /// as they aren't kept, None for empty blocks
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Stmt::Block(statements) => statements
+                .iter()
+                .filter_map(Stmt::span)
+                .reduce(|first, last| first.to(last)),
+            Stmt::Debugger(keyword) => Some(keyword.span),
+            Stmt::Expression(expr) | Stmt::Print(expr) => Some(expr.span()),
+            Stmt::Function(name, _, body) => Some(
+                body.iter()
+                    .filter_map(Stmt::span)
+                    .fold(name.span, |span, other| span.to(other)),
+            ),
+            Stmt::If(condition, then_branch, else_branch) => Some(
+                [then_branch, else_branch]
+                    .iter()
+                    .filter_map(|branch| branch.span())
+                    .fold(condition.span(), |span, other| span.to(other)),
+            ),
+            Stmt::Import(keyword, path, name) => Some(match &**name {
+                Some(name) => keyword.span.to(name.span),
+                None => keyword.span.to(path.span),
+            }),
+            Stmt::Test(keyword, name, _) => Some(keyword.span.to(name.span)),
+            Stmt::Throw(keyword, value) => Some(keyword.span.to(value.span())),
+            Stmt::Try(keyword, body, _, handler, finally) => Some(
+                [body, handler, finally]
+                    .iter()
+                    .flat_map(|statements| statements.iter())
+                    .filter_map(Stmt::span)
+                    .fold(keyword.span, |span, other| span.to(other)),
+            ),
+            Stmt::Var(name, initializer) => Some(name.span.to(initializer.span())),
+            Stmt::While(condition, body) => Some(match body.span() {
+                Some(span) => condition.span().to(span),
+                None => condition.span(),
+            }),
+        }
+    }
+}
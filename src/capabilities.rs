@@ -0,0 +1,282 @@
+/// What scripts may touch outside the interpreter
+///
+/// Natives are grouped by the capability they need. An interpreter built
+/// without one still defines its natives, but calling them fails with a
+/// permission error rather than an undefined variable. Reading and writing
+/// can also be restricted to directories.
+use crate::interpreter::{error, types, Callable, Interpreter, Interrupt};
+use crate::lexer::Token;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::{env, fs};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Read,
+    Write,
+    Env,
+    Process,
+    Clock,
+    Random,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 6] = [
+        Capability::Read,
+        Capability::Write,
+        Capability::Env,
+        Capability::Process,
+        Capability::Clock,
+        Capability::Random,
+    ];
+
+    /// Name used on the command line and in errors
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Read => "read",
+            Capability::Write => "write",
+            Capability::Env => "env",
+            Capability::Process => "process",
+            Capability::Clock => "clock",
+            Capability::Random => "random",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Capability> {
+        Capability::ALL
+            .iter()
+            .copied()
+            .find(|capability| capability.name() == name)
+    }
+
+    /// The natives needing it
+    pub fn natives(&self) -> &'static [&'static str] {
+        match self {
+            Capability::Read => &["readFile", "fileExists", "listDir"],
+            Capability::Write => &["writeFile", "appendFile"],
+            Capability::Env => &["args", "getenv"],
+            // the standard input is the process's too
+            Capability::Process => &["exit", "input", "readLine"],
+            Capability::Clock => &["clock", "now", "sleep"],
+            Capability::Random => &["random", "randomInt", "seed"],
+        }
+    }
+}
+
+/// Capabilities allowed, and the directories files may be read from
+/// and written to, anywhere when there are none
+#[derive(Clone, Debug, Default)]
+pub struct Permissions {
+    allowed: HashSet<Capability>,
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+}
+
+impl Permissions {
+    /// Every capability, files being restricted to no directory
+    pub fn all() -> Self {
+        Permissions {
+            allowed: Capability::ALL.iter().copied().collect(),
+            ..Permissions::default()
+        }
+    }
+
+    pub fn allow(&mut self, capability: Capability) {
+        self.allowed.insert(capability);
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.allowed.contains(&capability)
+    }
+
+    /// Allows reading, only in this directory and those given before
+    pub fn allow_read(&mut self, directory: &Path) {
+        self.allow(Capability::Read);
+        self.read.push(absolute(directory));
+    }
+
+    /// Allows writing, only in this directory and those given before
+    pub fn allow_write(&mut self, directory: &Path) {
+        self.allow(Capability::Write);
+        self.write.push(absolute(directory));
+    }
+
+    /// Fails with a permission error at `paren` unless the file can be read,
+    /// returns the path checked, which is the one to read
+    pub(crate) fn check_read(&self, path: &str, paren: &Token) -> Result<PathBuf, Interrupt> {
+        check_path(Capability::Read, &self.read, self, path, paren)
+    }
+
+    /// Fails with a permission error at `paren` unless the file can be written,
+    /// returns the path checked, which is the one to write
+    pub(crate) fn check_write(&self, path: &str, paren: &Token) -> Result<PathBuf, Interrupt> {
+        check_path(Capability::Write, &self.write, self, path, paren)
+    }
+}
+
+fn check_path(
+    capability: Capability,
+    directories: &[PathBuf],
+    permissions: &Permissions,
+    path: &str,
+    paren: &Token,
+) -> Result<PathBuf, Interrupt> {
+    let verb = match capability {
+        Capability::Write => "writing",
+        _ => "reading",
+    };
+
+    if !permissions.allows(capability) {
+        return Err(denied(
+            paren,
+            &format!(
+                "{} '{}' needs the {} capability",
+                verb,
+                path,
+                capability.name()
+            ),
+        ));
+    }
+
+    if directories.is_empty() {
+        return Ok(PathBuf::from(path));
+    }
+
+    let path = absolute(Path::new(path));
+
+    if !directories
+        .iter()
+        .any(|directory| path.starts_with(directory))
+    {
+        return Err(denied(
+            paren,
+            &format!(
+                "{} '{}' isn't allowed outside of the directories given",
+                verb,
+                path.display()
+            ),
+        ));
+    }
+
+    // links left are those to no file, which opening it would create anywhere
+    if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_symlink()) {
+        return Err(denied(
+            paren,
+            &format!(
+                "{} '{}' isn't allowed through a link to a missing file",
+                verb,
+                path.display()
+            ),
+        ));
+    }
+
+    Ok(path)
+}
+
+/// The path with symbolic links, `.` and `..` resolved, so that it can't
+/// escape a directory, as far as it exists
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+
+    // a file to create, in a directory which exists
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => absolute(parent).join(name),
+        // nothing exists, `..` must still be resolved
+        _ => {
+            let mut absolute = env::current_dir().unwrap_or_default();
+
+            for component in path.components() {
+                match component {
+                    Component::ParentDir => {
+                        absolute.pop();
+                    }
+                    Component::CurDir => (),
+                    component => absolute.push(component),
+                }
+            }
+
+            absolute
+        }
+    }
+}
+
+/// Sets up an interpreter allowed only what's given, nothing by default, e.g.
+/// `Interpreter::builder().allow(Capability::Clock).allow_read("data").build()`
+#[derive(Default)]
+pub struct Builder {
+    script_args: Vec<String>,
+    permissions: Permissions,
+}
+
+impl Builder {
+    /// Arguments returned by `args()`
+    pub fn args(mut self, script_args: Vec<String>) -> Self {
+        self.script_args = script_args;
+        self
+    }
+
+    pub fn allow(mut self, capability: Capability) -> Self {
+        self.permissions.allow(capability);
+        self
+    }
+
+    pub fn allow_all(mut self) -> Self {
+        for capability in Capability::ALL.iter().copied() {
+            self.permissions.allow(capability);
+        }
+        self
+    }
+
+    /// Allows reading files, only in the directories given this way
+    pub fn allow_read(mut self, directory: impl AsRef<Path>) -> Self {
+        self.permissions.allow_read(directory.as_ref());
+        self
+    }
+
+    /// Allows writing files, only in the directories given this way
+    pub fn allow_write(mut self, directory: impl AsRef<Path>) -> Self {
+        self.permissions.allow_write(directory.as_ref());
+        self
+    }
+
+    /// Replaces what was allowed so far
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    pub fn build(self) -> Interpreter {
+        Interpreter::with_permissions(self.script_args, self.permissions)
+    }
+}
+
+fn denied(paren: &Token, message: &str) -> Interrupt {
+    error(paren, "E0018", &format!("Permission denied: {}.", message))
+}
+
+/// Defined in place of a native needing a capability not allowed
+#[derive(Clone)]
+pub(crate) struct Denied {
+    pub name: &'static str,
+    pub capability: Capability,
+    pub arity: u8,
+}
+
+impl Callable for Denied {
+    fn arity(&self) -> u8 {
+        self.arity
+    }
+
+    fn call(&self, _: &mut Interpreter, _: Vec<types>, paren: &Token) -> Result<types, Interrupt> {
+        Err(denied(
+            paren,
+            &format!(
+                "'{}' needs the {} capability",
+                self.name,
+                self.capability.name()
+            ),
+        ))
+    }
+}
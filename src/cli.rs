@@ -0,0 +1,523 @@
+/// The `yayarlox` command: runs scripts or the REPL, and the subcommands
+/// working on scripts, with the options given on the command line
+use std::io::{self, Write};
+use std::time::Duration;
+use std::{env, fs, path, process, thread};
+
+use crate::ast::Stmt;
+use crate::capabilities::{Capability, Permissions};
+use crate::interpreter::{types, Interpreter, Interrupt};
+use crate::levels::{Level, Levels};
+use crate::limits::Limits;
+use crate::{
+    debugger, errors, explain, formatter, lex_and_parse, lint, lsp, printer, resolver, testing,
+};
+
+fn read_source(file_path: &path::Path) -> String {
+    fs::read_to_string(file_path).unwrap_or_else(|error| {
+        eprintln!("Couldn't read {}: {}", file_path.display(), error);
+        process::exit(exitcode::NOINPUT);
+    })
+}
+
+/// Prints the syntax tree of a script, as S-expressions
+/// or, with `--lox`, as equivalent Lox source
+fn print_ast(args: &[String]) {
+    let (lox, file) = match args {
+        [file] => (false, file),
+        [flag, file] if flag == "--lox" => (true, file),
+        _ => usage(),
+    };
+
+    let program = lex_and_parse(file, read_source(path::Path::new(file))).unwrap_or_else(|error| {
+        errors::summary(error);
+        process::exit(exitcode::DATAERR);
+    });
+
+    if lox {
+        print!("{}", printer::lox(&program.statements));
+    } else {
+        print!("{}", printer::sexpr(&program.statements));
+    }
+}
+
+/// Rewrites scripts in place, or with `--check` only lists
+/// those that aren't formatted, exiting with an error if any
+fn format_files(args: &[String]) {
+    let check = args.first().is_some_and(|arg| arg == "--check");
+    let files = if check { &args[1..] } else { args };
+
+    if files.is_empty() {
+        usage();
+    }
+
+    let mut unformatted = false;
+
+    for file in files {
+        let file_path = path::Path::new(file);
+        let code = read_source(file_path);
+
+        // formatting code that doesn't parse could change its meaning
+        if lex_and_parse(file, code.clone()).is_err() {
+            errors::summary(&format!("Couldn't format {}", file_path.display()));
+            process::exit(exitcode::DATAERR);
+        }
+
+        let formatted = match formatter::format(code.clone(), errors::NO_SOURCE) {
+            Some(formatted) => formatted,
+            None => process::exit(exitcode::DATAERR),
+        };
+
+        if formatted == code {
+            continue;
+        }
+
+        if check {
+            println!("{} is not formatted", file_path.display());
+            unformatted = true;
+        } else {
+            fs::write(file_path, formatted).unwrap_or_else(|error| {
+                eprintln!("Couldn't write {}: {}", file_path.display(), error);
+                process::exit(exitcode::IOERR);
+            });
+        }
+    }
+
+    if unformatted {
+        process::exit(exitcode::DATAERR);
+    }
+}
+
+/// Prints the warnings of the linter for each script,
+/// exiting with an error if there are any
+fn lint_files(files: &[String], levels: &Levels) {
+    if files.is_empty() {
+        usage();
+    }
+
+    let mut warned = false;
+
+    for file in files {
+        let code = read_source(path::Path::new(file));
+
+        let statements = lex_and_parse(file, code.clone())
+            .unwrap_or_else(|error| {
+                errors::summary(error);
+                process::exit(exitcode::DATAERR);
+            })
+            .statements;
+
+        if resolver::resolve(&statements).1 {
+            errors::summary("Aborting due to error while resolving.");
+            process::exit(exitcode::DATAERR);
+        }
+
+        let (levels, mut warnings) = levels.for_file(&code);
+        warnings.extend(lint::lint(&code, &statements));
+
+        warned |= levels.report(warnings).0;
+    }
+
+    if warned {
+        process::exit(exitcode::DATAERR);
+    }
+}
+
+/// Runs the tests of the scripts found in the paths, each in a fresh
+/// interpreter, then a summary, exiting with an error if any failed
+fn run_tests(paths: &[String], options: &Options) {
+    let paths = if paths.is_empty() {
+        vec![String::from(".")]
+    } else {
+        paths.to_vec()
+    };
+
+    let files = testing::discover(&paths).unwrap_or_else(|error| {
+        eprintln!("Couldn't search for tests: {}", error);
+        process::exit(exitcode::NOINPUT);
+    });
+
+    let (mut passed, mut failures) = (0, Vec::new());
+
+    for file in files {
+        let name = file.display().to_string();
+
+        let code = read_source(&file);
+
+        let statements = match lex_and_parse(&name, code.clone()) {
+            Ok(program) => program.statements,
+            Err(error) => {
+                errors::summary(error);
+                println!("{} ... FAILED", name);
+                failures.push(name);
+                continue;
+            }
+        };
+
+        // checked as when the file runs
+        let (levels, mut warnings) = options.levels.for_file(&code);
+        warnings.extend(lint::on_run(&code, &statements));
+
+        if levels.report(warnings).1 {
+            errors::summary("Aborting due to denied warnings.");
+            println!("{} ... FAILED", name);
+            failures.push(name);
+            continue;
+        }
+
+        let (setup, tests) = testing::split(statements);
+
+        for test in tests {
+            let mut interpreter = options.interpreter(Vec::new());
+            interpreter.set_path(&file);
+            interpreter.levels = levels.clone();
+
+            let line = match testing::run(&test, &setup, &mut interpreter) {
+                Ok(_) | Err(Interrupt::Exit(0)) => {
+                    println!("{} > {} ... ok", name, test.name);
+                    passed += 1;
+                    continue;
+                }
+                Err(Interrupt::Error(error)) => {
+                    println!("{} > {} ... FAILED", name, test.name);
+                    error.report();
+                    error.diagnostic.line
+                }
+                Err(Interrupt::Exit(code)) => {
+                    println!("{} > {} ... FAILED", name, test.name);
+                    eprintln!("Test exited with status {}.", code);
+                    test.line
+                }
+            };
+
+            failures.push(format!("{} > {} [line {}]", name, test.name, line));
+        }
+    }
+
+    println!();
+
+    if !failures.is_empty() {
+        println!("Failures:");
+        for failure in &failures {
+            println!("    {}", failure);
+        }
+        println!();
+    }
+
+    println!("{} passed, {} failed", passed, failures.len());
+
+    if !failures.is_empty() {
+        process::exit(exitcode::DATAERR);
+    }
+}
+
+/// Prints the explanation of an error code,
+/// or lists every code without one
+fn explain_code(args: &[String]) {
+    match args {
+        [] => {
+            for explanation in explain::CATALOGUE {
+                println!("{}  {}", explanation.code, explanation.title);
+            }
+        }
+        [code] => match explain::find(code) {
+            Some(explanation) => print!("{}", explain::render(explanation)),
+            None => {
+                eprintln!("No error code '{}', run without a code to list them.", code);
+                process::exit(exitcode::DATAERR);
+            }
+        },
+        _ => usage(),
+    }
+}
+
+/// Settings given on the command line, before the subcommand or the script
+#[derive(Default)]
+struct Options {
+    levels: Levels,
+    limits: Limits,
+    // only the capabilities allowed explicitly, rather than all
+    sandbox: bool,
+    permissions: Permissions,
+    seed: Option<u64>,
+}
+
+impl Options {
+    fn interpreter(&self, script_args: Vec<String>) -> Interpreter {
+        let mut permissions = self.permissions.clone();
+
+        // reading and writing may still be restricted to directories
+        if !self.sandbox {
+            for capability in Capability::ALL.iter().copied() {
+                permissions.allow(capability);
+            }
+        }
+
+        let mut interpreter = Interpreter::builder()
+            .args(script_args)
+            .permissions(permissions)
+            .build();
+
+        if let Some(seed) = self.seed {
+            interpreter.random.seed(seed);
+        }
+
+        interpreter.levels = self.levels.clone();
+        interpreter.limits = self.limits.clone();
+        interpreter
+    }
+}
+
+/// Runs a script, under the debugger if `debug` is set
+fn run_file(file_path: path::PathBuf, script_args: Vec<String>, debug: bool, options: &Options) {
+    let code = read_source(&file_path);
+
+    let mut interpreter = options.interpreter(script_args);
+    interpreter.set_path(&file_path);
+
+    if debug {
+        interpreter.debugger = Some(debugger::Debugger::default());
+    }
+
+    let program =
+        lex_and_parse(&file_path.display().to_string(), code.clone()).unwrap_or_else(|error| {
+            errors::summary(error);
+            process::exit(exitcode::DATAERR);
+        });
+
+    let (levels, mut warnings) = options.levels.for_file(&code);
+    warnings.extend(lint::on_run(&code, &program.statements));
+
+    if levels.report(warnings).1 {
+        errors::summary("Aborting due to denied warnings.");
+        process::exit(exitcode::DATAERR);
+    }
+
+    interpreter.levels = levels;
+
+    match interpreter.interpret(program) {
+        Ok(_) => (),
+        Err(Interrupt::Error(error)) => {
+            error.report();
+            process::exit(exitcode::DATAERR);
+        }
+        Err(Interrupt::Exit(code)) => process::exit(code),
+    }
+}
+
+#[allow(unused_must_use)]
+fn run_prompt(options: &Options) {
+    let mut interpreter = options.interpreter(Vec::new());
+
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+
+        // end of input
+        if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+            println!();
+            break;
+        }
+
+        // errors were already handled at this point,
+        // we default the expression to nil so it isn't
+        // printed
+
+        let program = match lex_and_parse("<stdin>", line) {
+            Ok(program) => program,
+            Err(_) => continue,
+        };
+
+        // the line is only quoted while functions declared in it may run
+        let source = program.source;
+        let declares_function = program.statements.iter().any(Stmt::declares_function);
+
+        let expr = match interpreter.interpret(program) {
+            Ok(expr) => expr,
+            Err(Interrupt::Error(error)) => {
+                error.report();
+                types::nil
+            }
+            Err(Interrupt::Exit(code)) => process::exit(code),
+        };
+
+        if !declares_function {
+            errors::remove_source(source);
+        }
+
+        if expr != types::nil {
+            println!("{:?}", expr);
+        }
+    }
+}
+
+fn parse_option<T: std::str::FromStr>(value: &str) -> T {
+    value.parse().unwrap_or_else(|_| usage())
+}
+
+fn usage() -> ! {
+    let name = env::args()
+        .next()
+        .unwrap_or_else(|| String::from("yayarlox"));
+
+    eprintln!("Usage: {} [options] [script [arguments...]]", name);
+    eprintln!("       {} [options] run|-- <script> [arguments...]", name);
+    eprintln!("       {} ast [--lox] <script>", name);
+    eprintln!("       {} fmt [--check] <scripts...>", name);
+    eprintln!("       {} lint <scripts...>", name);
+    eprintln!("       {} test [scripts or directories...]", name);
+    eprintln!("       {} explain [code]", name);
+    eprintln!("       {} lsp", name);
+    eprintln!("       {} debug <script> [arguments...]", name);
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --error-format=human|json   how diagnostics are printed");
+    eprintln!("  --allow=<lints>             ignore these warnings (comma separated)");
+    eprintln!("  --warn=<lints>              report these warnings");
+    eprintln!("  --deny=<lints>              report these warnings as errors");
+    eprintln!("  --deny-warnings             report every warning as an error");
+    eprintln!("  --seed=<n>                  seed of random(), for reproducible runs");
+    eprintln!("  --sandbox                   allow scripts only the capabilities below,");
+    eprintln!("                              as giving any of them does");
+    eprintln!("  --allow-read[=<dirs>]       reading files, only in these directories if given");
+    eprintln!("  --allow-write[=<dirs>]      writing files, only in these directories if given");
+    eprintln!("  --allow-env                 args() and getenv()");
+    eprintln!("  --allow-process             exit(), input() and readLine()");
+    eprintln!("  --allow-clock               clock(), now() and sleep()");
+    eprintln!("  --allow-random              random(), randomInt() and seed()");
+    eprintln!("  --max-steps=<n>             statements and expressions a script may run");
+    eprintln!("  --max-time=<seconds>        time a script may run for");
+    eprintln!("  --max-depth=<n>             calls running at once, 10000 by default");
+    eprintln!("  --max-string-bytes=<n>      bytes of the strings a script may make");
+    process::exit(exitcode::USAGE);
+}
+
+// enough for the default call depth limit, in debug builds too
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// default call depth of scripts run by the thread `main` starts, deeper
+// than `limits::EMBEDDED_DEPTH` as they have a large stack
+const MAIN_THREAD_DEPTH: usize = 10_000;
+
+pub fn main() {
+    let main = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .unwrap_or_else(|error| {
+            eprintln!("Couldn't start the interpreter: {}", error);
+            process::exit(exitcode::OSERR);
+        });
+
+    // it panicked, which was already reported
+    if main.join().is_err() {
+        process::exit(exitcode::SOFTWARE);
+    }
+}
+
+fn run() {
+    let mut args: Vec<String> = env::args().collect();
+
+    let mut options = Options::default();
+    options.limits.depth = Some(MAIN_THREAD_DEPTH);
+
+    // options come before the subcommand or the script
+    // `--` ends the options, what follows is the script
+    while let Some(option) = args
+        .get(1)
+        .filter(|arg| arg.starts_with("--") && *arg != "--")
+        .cloned()
+    {
+        let (name, value) = option.split_once('=').unwrap_or((&option, ""));
+
+        match name {
+            "--error-format" => errors::set_format(match value {
+                "human" => errors::Format::Human,
+                "json" => errors::Format::Json,
+                _ => usage(),
+            }),
+            "--allow" | "--warn" | "--deny" => {
+                let level = Level::from_name(&name[2..]).unwrap_or_else(|| usage());
+
+                if let Err(lint) = options.levels.set(value, level) {
+                    eprintln!("Unknown lint '{}'.", lint);
+                    process::exit(exitcode::USAGE);
+                }
+            }
+            "--deny-warnings" => options.levels.deny_warnings = true,
+            "--seed" => options.seed = Some(parse_option(value)),
+            "--sandbox" => options.sandbox = true,
+            // allowing some capabilities means not the others
+            "--allow-read" | "--allow-write" if !value.is_empty() => {
+                options.sandbox = true;
+
+                for directory in value.split(',') {
+                    let directory = path::Path::new(directory);
+
+                    if name == "--allow-read" {
+                        options.permissions.allow_read(directory);
+                    } else {
+                        options.permissions.allow_write(directory);
+                    }
+                }
+            }
+            "--allow-read" | "--allow-write" | "--allow-env" | "--allow-process"
+            | "--allow-clock" | "--allow-random" => match Capability::from_name(&name[8..]) {
+                Some(capability) => {
+                    options.sandbox = true;
+                    options.permissions.allow(capability);
+                }
+                None => usage(),
+            },
+            "--max-steps" => options.limits.steps = Some(parse_option(value)),
+            "--max-time" => {
+                let seconds = parse_option(value);
+                let time = Duration::try_from_secs_f64(seconds).unwrap_or_else(|_| usage());
+
+                options.limits.time = Some(time);
+            }
+            "--max-depth" => options.limits.depth = Some(parse_option(value)),
+            "--max-string-bytes" => options.limits.string_bytes = Some(parse_option(value)),
+            _ => usage(),
+        }
+
+        args.remove(1);
+    }
+
+    match args.get(1).map(String::as_str) {
+        None => run_prompt(&options),
+        Some("ast") => print_ast(&args[2..]),
+        Some("fmt") => format_files(&args[2..]),
+        Some("lint") => lint_files(&args[2..], &options.levels),
+        Some("test") => run_tests(&args[2..], &options),
+        Some("explain") => explain_code(&args[2..]),
+        Some("lsp") => process::exit(lsp::run()),
+        Some("debug") => match args.get(2) {
+            Some(script) => run_file(
+                path::PathBuf::from(script),
+                args[3..].to_vec(),
+                true,
+                &options,
+            ),
+            None => usage(),
+        },
+        // scripts named like a subcommand must be given this way
+        Some("run") | Some("--") => match args.get(2) {
+            Some(script) => run_file(
+                path::PathBuf::from(script),
+                args[3..].to_vec(),
+                false,
+                &options,
+            ),
+            None => usage(),
+        },
+        // anything after the script's path is left to the script
+        Some(script) => run_file(
+            path::PathBuf::from(script),
+            args[2..].to_vec(),
+            false,
+            &options,
+        ),
+    }
+}
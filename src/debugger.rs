@@ -0,0 +1,250 @@
+/// Interactive step debugger
+///
+/// The interpreter calls `before` ahead of every statement. Execution pauses
+/// when the debugger is stepping, on breakpoints and on `debugger;` statements,
+/// then commands are read from stdin until execution resumes.
+/// Several statements on a single line only pause once. Lines are shown
+/// from the code registered for errors, so imported files are too.
+use crate::ast::Stmt;
+use crate::interpreter::{types, Interpreter, Interrupt};
+use crate::{errors, lexer, parser};
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+const HELP: &str = "\
+Commands:
+  break <line>, b      set a breakpoint
+  delete <line>, d     remove a breakpoint
+  breakpoints          list breakpoints
+  step, s              run until the next line, entering calls
+  next, n              run until the next line, over calls
+  finish, f            run until the current function returns
+  continue, c          run until a breakpoint
+  print <expr>, p      evaluate an expression in the current scope
+  vars, v              list the variables of every scope
+  backtrace, bt        list the function calls being executed
+  list, l              show the source around the current line
+  quit, q              stop the script
+  help, h              show this message";
+
+#[derive(Default)]
+enum Mode {
+    Continue,
+    // which the debugger starts with, to pause on the first statement
+    #[default]
+    StepInto,
+    // call depth at which stepping started
+    StepOver(usize),
+    StepOut(usize),
+}
+
+/// Lines are given as the source id of their code and their number
+#[derive(Default)]
+pub struct Debugger {
+    breakpoints: BTreeSet<(usize, usize)>,
+    mode: Mode,
+
+    // line and call depth of the last statement met
+    last: ((usize, usize), usize),
+}
+
+impl Debugger {
+    fn should_pause(&self, line: (usize, usize), depth: usize) -> bool {
+        match self.mode {
+            Mode::Continue => self.breakpoints.contains(&line),
+            Mode::StepInto => true,
+            Mode::StepOver(start) => depth <= start,
+            Mode::StepOut(start) => depth < start,
+        }
+    }
+
+    fn show_line(&self, (source, line): (usize, usize)) {
+        match errors::source_line(source, line) {
+            Some(text) => println!("[line {}] {}", line, text.trim()),
+            None => println!("[line {}]", line),
+        }
+    }
+
+    fn list(&self, (source, line): (usize, usize)) {
+        let first = line.saturating_sub(3).max(1);
+
+        for number in first..=line + 3 {
+            let text = match errors::source_line(source, number) {
+                Some(text) => text,
+                None => break,
+            };
+
+            let marker = if number == line { "->" } else { "  " };
+            let breakpoint = if self.breakpoints.contains(&(source, number)) {
+                "*"
+            } else {
+                " "
+            };
+
+            println!("{}{}{:>4} {}", marker, breakpoint, number, text);
+        }
+    }
+}
+
+/// Called by the interpreter before executing a statement
+pub fn before(interpreter: &mut Interpreter, stmt: &Stmt) -> Result<(), Interrupt> {
+    // blocks pause on their first statement instead
+    if let Stmt::Block(_) = stmt {
+        return Ok(());
+    }
+
+    let line = match (stmt.span(), stmt.line()) {
+        (Some(span), Some(line)) => (span.source, line),
+        _ => return Ok(()),
+    };
+
+    let depth = interpreter.call_stack.len();
+
+    let mut debugger = match interpreter.debugger.take() {
+        Some(debugger) => debugger,
+        None => return Ok(()),
+    };
+
+    let moved = debugger.last != (line, depth);
+    debugger.last = (line, depth);
+
+    let forced = matches!(stmt, Stmt::Debugger(_));
+
+    let result = if forced || (moved && debugger.should_pause(line, depth)) {
+        pause(interpreter, &mut debugger, line)
+    } else {
+        Ok(())
+    };
+
+    interpreter.debugger = Some(debugger);
+
+    result
+}
+
+fn pause(
+    interpreter: &mut Interpreter,
+    debugger: &mut Debugger,
+    line: (usize, usize),
+) -> Result<(), Interrupt> {
+    debugger.show_line(line);
+
+    loop {
+        print!("(debug) ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+
+        // end of input: let the script run to its end
+        if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            println!();
+            debugger.mode = Mode::Continue;
+            debugger.breakpoints.clear();
+            return Ok(());
+        }
+
+        let input = input.trim();
+        let (command, argument) = match input.find(' ') {
+            Some(index) => (&input[..index], input[index + 1..].trim()),
+            None => (input, ""),
+        };
+
+        let depth = interpreter.call_stack.len();
+
+        match command {
+            "step" | "s" => {
+                debugger.mode = Mode::StepInto;
+                return Ok(());
+            }
+            "next" | "n" => {
+                debugger.mode = Mode::StepOver(depth);
+                return Ok(());
+            }
+            "finish" | "f" => {
+                if depth == 0 {
+                    println!("Not in a function.");
+                } else {
+                    debugger.mode = Mode::StepOut(depth);
+                    return Ok(());
+                }
+            }
+            "continue" | "c" => {
+                debugger.mode = Mode::Continue;
+                return Ok(());
+            }
+            "quit" | "q" => return Err(Interrupt::Exit(0)),
+
+            // in the file being run at the moment
+            "break" | "b" => match argument.parse::<usize>() {
+                Ok(number) if number > 0 => {
+                    debugger.breakpoints.insert((line.0, number));
+                    println!("Breakpoint set at line {}.", number);
+                }
+                _ => println!("Expected a line number."),
+            },
+            "delete" | "d" => match argument.parse::<usize>() {
+                Ok(number) if debugger.breakpoints.remove(&(line.0, number)) => {
+                    println!("Breakpoint at line {} removed.", number)
+                }
+                _ => println!("No breakpoint at '{}'.", argument),
+            },
+            "breakpoints" => {
+                for breakpoint in debugger.breakpoints.iter() {
+                    debugger.show_line(*breakpoint);
+                }
+            }
+
+            "print" | "p" => print_expression(interpreter, argument)?,
+            "vars" | "v" => {
+                let scopes = interpreter.scopes();
+                let count = scopes.len();
+
+                for (i, scope) in scopes.iter().enumerate() {
+                    // natives would only clutter the global scope
+                    let names: Vec<String> = scope
+                        .iter()
+                        .filter(|(_, value)| !matches!(value, types::native_function(_)))
+                        .map(|(name, value)| format!("{} = {:?}", name, value))
+                        .collect();
+
+                    let title = if i + 1 == count { "global" } else { "local" };
+                    println!("{} scope: {}", title, names.join(", "));
+                }
+            }
+            "backtrace" | "bt" => {
+                println!("at line {}", line.1);
+                for frame in interpreter.call_stack.iter().rev() {
+                    println!("in {} called from line {}", frame.function, frame.line);
+                }
+            }
+            "list" | "l" => debugger.list(line),
+
+            "help" | "h" => println!("{}", HELP),
+            "" => (),
+            _ => println!("Unknown command '{}', try 'help'.", command),
+        }
+    }
+}
+
+fn print_expression(interpreter: &mut Interpreter, code: &str) -> Result<(), Interrupt> {
+    let (tokens, had_error) = lexer::scan(format!("{};", code), errors::NO_SOURCE);
+    if had_error {
+        return Ok(());
+    }
+
+    let (statements, had_error) = parser::parse(tokens);
+    if had_error {
+        return Ok(());
+    }
+
+    match statements.into_iter().next() {
+        Some(Stmt::Expression(expr)) => match interpreter.evaluate(*expr) {
+            Ok(value) => println!("{:?}", value),
+            // the script itself goes on
+            Err(Interrupt::Error(error)) => error.report(),
+            Err(exit) => return Err(exit),
+        },
+        _ => println!("Expected an expression."),
+    }
+
+    Ok(())
+}
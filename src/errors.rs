@@ -1,7 +1,474 @@
-pub fn error(line: usize, message: &str) {
-    report(line, "", message);
+use crate::json::Value;
+use crate::lexer::{Span, Token, TokenVariant};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Source id of code that isn't registered,
+/// its diagnostics are printed without any excerpt
+pub(crate) const NO_SOURCE: usize = usize::MAX;
+
+/// A secondary note pointing at some code, e.g. "variable declared here"
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// How diagnostics are printed on stderr
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Human,
+    /// One JSON object per line, see `to_json`
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A reported error or warning, as collected by `capture`
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // stable identifier, e.g. W0001 for lints
+    pub code: Option<String>,
+    pub line: usize,
+    pub location: String,
+    pub message: String,
+    // the offending code, underlined when printed
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    // printed after everything else, e.g. a traceback
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(line: usize, location: &str, message: &str) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            line,
+            location: String::from(location),
+            message: String::from(message),
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Error located at a token, which is underlined
+    pub(crate) fn at(token: &Token, message: &str) -> Self {
+        let location = if token.class == TokenVariant::Eof {
+            String::from(" at end")
+        } else {
+            format!(" at '{}'", token.lexeme)
+        };
+
+        Diagnostic::new(token.line, &location, message).with_span(token.span)
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(String::from(code));
+        self
+    }
+
+    pub(crate) fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub(crate) fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push(Label {
+            span,
+            message: String::from(message),
+        });
+        self
+    }
+}
+
+struct Source {
+    name: String,
+    text: String,
+}
+
+thread_local! {
+    // Some while errors are being captured rather than printed
+    static CAPTURED: RefCell<Option<Vec<Diagnostic>>> = const { RefCell::new(None) };
+
+    // code diagnostics may point into, by source id
+    static SOURCES: RefCell<HashMap<usize, Source>> = RefCell::new(HashMap::new());
+    // ids aren't reused, so that code forgotten is never mistaken for newer code
+    static NEXT_SOURCE: Cell<usize> = const { Cell::new(0) };
+
+    static FORMAT: Cell<Format> = const { Cell::new(Format::Human) };
+}
+
+pub fn set_format(format: Format) {
+    FORMAT.with(|current| current.set(format));
+}
+
+/// Prints a message about diagnostics already emitted, e.g.
+/// "Aborting due to error while parsing.", left out of JSON output
+pub fn summary(message: &str) {
+    if FORMAT.with(Cell::get) == Format::Human {
+        eprintln!("{}", message);
+    }
+}
+
+/// Registers code under a name (usually its file's),
+/// returns the source id its tokens should carry
+pub fn add_source(name: &str, text: &str) -> usize {
+    let id = NEXT_SOURCE.with(|next| next.replace(next.get() + 1));
+
+    SOURCES.with(|sources| {
+        sources.borrow_mut().insert(
+            id,
+            Source {
+                name: String::from(name),
+                text: String::from(text),
+            },
+        )
+    });
+
+    id
+}
+
+/// Forgets registered code, diagnostics pointing into it
+/// being printed without excerpts from then on
+pub(crate) fn remove_source(source: usize) {
+    SOURCES.with(|sources| sources.borrow_mut().remove(&source));
+}
+
+/// Forgets all the code registered so far, which is kept for excerpts
+/// as long as functions parsed from it may still run and fail. Meant for
+/// hosts running many scripts, once they're done with those parsed before.
+pub fn clear_sources() {
+    SOURCES.with(|sources| sources.borrow_mut().clear());
+}
+
+/// Text of a line, counted from 1, of registered code
+pub(crate) fn source_line(source: usize, line: usize) -> Option<String> {
+    SOURCES.with(|sources| {
+        let sources = sources.borrow();
+        let text = sources
+            .get(&source)?
+            .text
+            .lines()
+            .nth(line.checked_sub(1)?)?;
+
+        Some(String::from(text))
+    })
+}
+
+pub fn emit(diagnostic: Diagnostic) {
+    let diagnostic = CAPTURED.with(|captured| match &mut *captured.borrow_mut() {
+        Some(diagnostics) => {
+            diagnostics.push(diagnostic);
+            None
+        }
+        None => Some(diagnostic),
+    });
+
+    if let Some(diagnostic) = diagnostic {
+        match FORMAT.with(Cell::get) {
+            Format::Human => eprint!("{}", render(&diagnostic)),
+            Format::Json => eprintln!("{}", to_json(&diagnostic)),
+        }
+    }
+}
+
+/// Runs `f`, collecting the errors it reports instead of printing them
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Diagnostic>) {
+    let outer = CAPTURED.with(|captured| captured.replace(Some(Vec::new())));
+
+    let result = f();
+
+    let diagnostics = CAPTURED.with(|captured| captured.replace(outer));
+
+    (result, diagnostics.unwrap_or_default())
+}
+
+/// Line and column, both counted from 1, of a byte offset
+pub(crate) fn position(text: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Candidate most similar to a misspelled `name`, if any is close enough:
+/// within a third of its length in edits, so short names get no suggestion
+pub(crate) fn closest<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let limit = name.chars().count() / 3;
+
+    candidates
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        // ties go to the first candidate, the innermost one
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// Levenshtein distance in characters, swapping two adjacent
+// characters counting as a single edit (e.g. "pritn" and "print")
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+
+    // distances[i][j] is the distance between a[..i] and b[..j]
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            distances[i][j] = if i == 0 || j == 0 {
+                i + j
+            } else {
+                let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+                let mut distance = substitution
+                    .min(distances[i - 1][j] + 1)
+                    .min(distances[i][j - 1] + 1);
+
+                if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                    distance = distance.min(distances[i - 2][j - 2] + 1);
+                }
+
+                distance
+            };
+        }
+    }
+
+    distances[a.len()][b.len()]
 }
 
-pub fn report(line: usize, location: &str, message: &str) {
-    eprintln!("[line {}] Error{}: {}", line, location, message);
+/// The diagnostic as a JSON object, e.g.
+///
+/// ```text
+/// {"severity":"warning","code":"W0003","message":"'a' shadows a variable of an outer scope.",
+///  "file":"example.lox","line":15,"column":6,"span":{"start":234,"end":235},
+///  "related":[{"message":"shadowed declaration here","file":"example.lox",
+///              "line":5,"column":5,"span":{"start":72,"end":73}}],
+///  "notes":[]}
+/// ```
+///
+/// `code`, `file`, `column` and `span` are null when unknown,
+/// in which case `line` may still be set
+pub(crate) fn to_json(diagnostic: &Diagnostic) -> Value {
+    let severity = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    let mut fields = vec![
+        ("severity", Value::string(severity)),
+        (
+            "code",
+            diagnostic
+                .code
+                .as_deref()
+                .map_or(Value::Null, Value::string),
+        ),
+        ("message", Value::string(&diagnostic.message)),
+    ];
+    fields.extend(location(diagnostic.line, diagnostic.span));
+    fields.push((
+        "related",
+        Value::Array(
+            diagnostic
+                .labels
+                .iter()
+                .map(|label| {
+                    let mut fields = vec![("message", Value::string(&label.message))];
+                    fields.extend(location(0, Some(label.span)));
+                    Value::object(fields)
+                })
+                .collect(),
+        ),
+    ));
+    fields.push((
+        "notes",
+        Value::Array(
+            diagnostic
+                .notes
+                .iter()
+                .map(|note| Value::string(note))
+                .collect(),
+        ),
+    ));
+
+    Value::object(fields)
+}
+
+// file, line, column and span fields, `line` being used
+// when the span's source isn't registered
+fn location(line: usize, span: Option<Span>) -> Vec<(&'static str, Value)> {
+    let found = span.and_then(|span| {
+        SOURCES.with(|sources| {
+            let sources = sources.borrow();
+            let source = sources.get(&span.source)?;
+            let (line, column) = position(&source.text, span.start);
+
+            Some((source.name.clone(), line, column, span))
+        })
+    });
+
+    match found {
+        Some((file, line, column, span)) => vec![
+            ("file", Value::string(&file)),
+            ("line", Value::Number(line as f64)),
+            ("column", Value::Number(column as f64)),
+            (
+                "span",
+                Value::object(vec![
+                    ("start", Value::Number(span.start as f64)),
+                    ("end", Value::Number(span.end as f64)),
+                ]),
+            ),
+        ],
+        None => vec![
+            ("file", Value::Null),
+            ("line", Value::Number(line as f64)),
+            ("column", Value::Null),
+            ("span", Value::Null),
+        ],
+    }
+}
+
+/// The diagnostic as printed, e.g.
+///
+/// ```text
+/// [line 2] Error at 'b': Already a variable with this name in this scope.
+///   --> script.lox:2:17
+///    |
+///  2 | { var b = 1; var b = 2; }
+///    |                  ^
+///    |
+///  2 | { var b = 1; var b = 2; }
+///    |       - previous declaration here
+/// ```
+pub fn render(diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => "Error",
+        Severity::Warning => "Warning",
+    };
+    let code = match &diagnostic.code {
+        Some(code) => format!("[{}]", code),
+        None => String::new(),
+    };
+
+    let mut result = format!(
+        "[line {}] {}{}{}: {}\n",
+        diagnostic.line, severity, code, diagnostic.location, diagnostic.message
+    );
+
+    if let Some(span) = diagnostic.span {
+        excerpts(&mut result, diagnostic, span);
+    }
+
+    for note in diagnostic.notes.iter() {
+        result.push_str(&format!("  {}\n", note));
+    }
+
+    result
+}
+
+// The code the diagnostic points at, then its labels
+fn excerpts(result: &mut String, diagnostic: &Diagnostic, span: Span) {
+    SOURCES.with(|sources| {
+        let sources = sources.borrow();
+
+        let source = match sources.get(&span.source) {
+            Some(source) => source,
+            None => return,
+        };
+
+        let (line, column) = position(&source.text, span.start);
+
+        // every excerpt shares the margin of the widest line number
+        let width = std::iter::once(line)
+            .chain(
+                diagnostic
+                    .labels
+                    .iter()
+                    .map(|label| position(&source.text, label.span.start).0),
+            )
+            .max()
+            .unwrap_or(line)
+            .to_string()
+            .len();
+
+        result.push_str(&format!(
+            "{:width$}--> {}:{}:{}\n",
+            "",
+            source.name,
+            line,
+            column,
+            width = width + 1
+        ));
+
+        excerpt(result, &source.text, span, '^', "", width);
+
+        for label in diagnostic.labels.iter() {
+            if let Some(label_source) = sources.get(&label.span.source) {
+                excerpt(
+                    result,
+                    &label_source.text,
+                    label.span,
+                    '-',
+                    &label.message,
+                    width,
+                );
+            }
+        }
+    });
+}
+
+// A source line, with the span underlined
+fn excerpt(result: &mut String, text: &str, span: Span, marker: char, note: &str, width: usize) {
+    let (line, column) = position(text, span.start);
+    let code = text.lines().nth(line - 1).unwrap_or("");
+
+    // spans over several lines are underlined up to the end of the first one
+    let available = code.chars().count().saturating_sub(column - 1);
+    let length = text[span.start.min(text.len())..span.end.min(text.len())]
+        .chars()
+        .count()
+        .min(available)
+        .max(1);
+
+    result.push_str(&format!("{:width$} |\n", "", width = width));
+    result.push_str(&format!("{:>width$} | {}\n", line, code, width = width));
+    // tabs are kept so that the underline lines up with the code
+    let padding: String = code
+        .chars()
+        .take(column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    result.push_str(&format!(
+        "{:width$} | {}{}",
+        "",
+        padding,
+        marker.to_string().repeat(length),
+        width = width
+    ));
+
+    if !note.is_empty() {
+        result.push(' ');
+        result.push_str(note);
+    }
+
+    result.push('\n');
 }
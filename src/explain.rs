@@ -0,0 +1,419 @@
+/// Catalogue of the codes diagnostics carry, for `yayarlox explain`
+///
+/// Codes start with the pass reporting them: L for the lexer, P for the
+/// parser, R for the resolver, E for runtime errors and W for lint warnings.
+/// They are never reused for another diagnostic.
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    // code triggering the diagnostic
+    pub example: &'static str,
+    // how the example could be fixed
+    pub fix: &'static str,
+}
+
+pub const CATALOGUE: &[Explanation] = &[
+    Explanation {
+        code: "L0001",
+        title: "unexpected character",
+        description: "The character can't start any token. Outside of strings and comments, \
+            only letters, digits, underscores, whitespace and the operators of the language are allowed.",
+        example: "var price = 12 € 3;",
+        fix: "var price = 12 * 3;",
+    },
+    Explanation {
+        code: "L0002",
+        title: "invalid number",
+        description: "The digits of a number literal couldn't be read as a number. \
+            Every literal the lexer accepts should be valid, so this is a bug of the interpreter.",
+        example: "",
+        fix: "Please report the number causing it.",
+    },
+    Explanation {
+        code: "L0003",
+        title: "unterminated string",
+        description: "A string is missing its closing quote, so it runs until the end of the file. \
+            Strings may span several lines, so the error can point far above the end of the file.",
+        example: "print \"Hello;",
+        fix: "print \"Hello\";",
+    },
+    Explanation {
+        code: "P0001",
+        title: "expected expression",
+        description: "An expression was expected, e.g. a value, a variable or a call, \
+            but the code has something else or nothing at all.",
+        example: "print ;",
+        fix: "print \"something\";",
+    },
+    Explanation {
+        code: "P0002",
+        title: "missing semicolon",
+        description: "Statements other than blocks, `if`, `while`, `for` and function \
+            declarations end with a semicolon. When the statement starts with a misspelled \
+            keyword, e.g. `whille`, the keyword is suggested.",
+        example: "var a = 1\nprint a;",
+        fix: "var a = 1;\nprint a;",
+    },
+    Explanation {
+        code: "P0003",
+        title: "missing parenthesis or brace",
+        description: "A parenthesis or brace is missing, e.g. around the condition of an `if`, \
+            after the arguments of a call or at the end of a block.",
+        example: "if (a > 1 {\n    print a;\n}",
+        fix: "if (a > 1) {\n    print a;\n}",
+    },
+    Explanation {
+        code: "P0004",
+        title: "expected name",
+        description: "`var` and `fun` must be followed by the name being declared, \
+            which can't be a keyword.",
+        example: "var class = 1;",
+        fix: "var category = 1;",
+    },
+    Explanation {
+        code: "P0005",
+        title: "too many arguments",
+        description: "Functions can't have more than 255 parameters, \
+            and calls can't pass more than 255 arguments.",
+        example: "f(a1, a2, ..., a256);",
+        fix: "Group the values in fewer arguments.",
+    },
+    Explanation {
+        code: "P0006",
+        title: "invalid assignment target",
+        description: "Only variables can be assigned to, not the result of an expression.",
+        example: "a + b = 1;",
+        fix: "a = 1 - b;",
+    },
+    Explanation {
+        code: "P0007",
+        title: "code nested too deeply",
+        description: "Statements and expressions can't be nested more than 200 levels deep, \
+            to keep the interpreter from overflowing its stack. Chains like `1 + 2 + 3` \
+            or `f()().a` nest too, and can't have more than 400 operators, calls and \
+            properties, counting those of the chains around them. \
+            The rest of the file isn't parsed.",
+        example: "print ((((((...1...))))));\nprint 1 + 1 + 1 + ... + 1;",
+        fix: "Split the expression using intermediate variables or functions.",
+    },
+    Explanation {
+        code: "P0008",
+        title: "missing import path",
+        description: "`import` must be followed by the path of the file, as a string. \
+            Relative paths start from the directory of the importing file.",
+        example: "import lib;",
+        fix: "import \"lib.lox\";",
+    },
+    Explanation {
+        code: "R0001",
+        title: "variable declared twice in a scope",
+        description: "A block or function can't declare the same name twice. \
+            Redeclaring globals is allowed though.",
+        example: "{\n    var a = 1;\n    var a = 2;\n}",
+        fix: "{\n    var a = 1;\n    a = 2;\n}",
+    },
+    Explanation {
+        code: "R0002",
+        title: "variable read in its own initializer",
+        description: "A local variable exists from its declaration on, but can't be used \
+            before it has a value.",
+        example: "var a = 1;\n{\n    var a = a + 1;\n}",
+        fix: "var a = 1;\n{\n    var b = a + 1;\n}",
+    },
+    Explanation {
+        code: "R0003",
+        title: "test not at the top level",
+        description: "`test \"name\" { ... }` blocks are only found and run by `yayarlox test` \
+            at the top level of a file, so they can't be nested in blocks or functions.",
+        example: "fun helper() {\n    test \"sum\" {\n        assertEqual(1 + 1, 2);\n    }\n}",
+        fix: "test \"sum\" {\n    assertEqual(1 + 1, 2);\n}",
+    },
+    Explanation {
+        code: "E0001",
+        title: "undefined variable",
+        description: "The variable isn't declared in any enclosing scope at this point. \
+            A close name in scope is suggested when there is one.",
+        example: "var counter = 1;\nprint countr;",
+        fix: "var counter = 1;\nprint counter;",
+    },
+    Explanation {
+        code: "E0002",
+        title: "value isn't callable",
+        description: "Only functions can be called, the value being called is something else.",
+        example: "var a = 1;\na();",
+        fix: "fun a() {}\na();",
+    },
+    Explanation {
+        code: "E0003",
+        title: "wrong number of arguments",
+        description: "A function must be called with as many arguments as it has parameters.",
+        example: "fun add(a, b) { print a + b; }\nadd(1);",
+        fix: "fun add(a, b) { print a + b; }\nadd(1, 2);",
+    },
+    Explanation {
+        code: "E0004",
+        title: "operand of the wrong type",
+        description: "Arithmetic operators only apply to numbers, while `+` and \
+            comparisons also apply to two strings. The types of the operands are \
+            shown below them.",
+        example: "print \"total: \" + 3;",
+        fix: "print \"total: 3\";",
+    },
+    Explanation {
+        code: "E0005",
+        title: "invalid argument to a native function",
+        description: "A function implemented in Rust received a value it can't use, \
+            e.g. of the wrong type or out of range.",
+        example: "print get(args(), -1);",
+        fix: "print get(args(), 0);",
+    },
+    Explanation {
+        code: "E0006",
+        title: "couldn't print",
+        description: "Writing to the standard output failed, \
+            e.g. because it's piped into a program which already exited.",
+        example: "yayarlox script.lox | head -n 1",
+        fix: "Read the whole output, or have the script print less.",
+    },
+    Explanation {
+        code: "E0007",
+        title: "malformed syntax tree",
+        description: "The syntax tree holds a node the parser never produces, \
+            e.g. a literal made of an operator. This is a bug of the interpreter.",
+        example: "",
+        fix: "Please report the script causing it.",
+    },
+    Explanation {
+        code: "E0008",
+        title: "uncaught exception",
+        description: "A value was thrown with `throw` and no enclosing `try` caught it. \
+            Wrap the code raising it in a `try` with a `catch` to handle it.",
+        example: "throw error(\"Not found\");",
+        fix: "try {\n    throw error(\"Not found\");\n} catch (e) {\n    print errorMessage(e);\n}",
+    },
+    Explanation {
+        code: "E0009",
+        title: "input or output failed",
+        description: "A native reading or writing files or the standard input failed. \
+            The message gives the path and the reason reported by the system, \
+            e.g. a missing file or a lack of permissions.",
+        example: "print readFile(\"missing.txt\");",
+        fix: "if (fileExists(\"missing.txt\")) print readFile(\"missing.txt\");",
+    },
+    Explanation {
+        code: "E0010",
+        title: "import cycle",
+        description: "A file imports itself, directly or through other files. \
+            Each file is run once, when first imported, so a file can't use \
+            one which is still running. The message lists the files of the cycle.",
+        example: "// a.lox\nimport \"b.lox\";\n// b.lox\nimport \"a.lox\";",
+        fix: "// a.lox\nimport \"b.lox\";\n// b.lox, moving what a.lox needs to a third file\nimport \"c.lox\";",
+    },
+    Explanation {
+        code: "E0011",
+        title: "imported file has errors",
+        description: "A file imported couldn't be lexed or parsed, or has warnings denied \
+            by its pragmas or the command line. \
+            Its errors are reported before this one, pointing into it.",
+        example: "// lib.lox\nprint ;\n// main.lox\nimport \"lib.lox\";",
+        fix: "// lib.lox\nprint \"loaded\";\n// main.lox\nimport \"lib.lox\";",
+    },
+    Explanation {
+        code: "E0012",
+        title: "undefined member",
+        description: "A member was read with `.` on a value which isn't a module, \
+            or the module imported has no global variable of that name.",
+        example: "import \"lib.lox\" as lib;\nprint lib.undefined;",
+        fix: "import \"lib.lox\" as lib;\nprint lib.defined;",
+    },
+    Explanation {
+        code: "E0013",
+        title: "assertion failed",
+        description: "`assert(condition, message)` was given a falsy condition, or \
+            `assertEqual(actual, expected)` two different values. Like other runtime \
+            errors, failed assertions can be caught, and make a test fail otherwise.",
+        example: "assertEqual(len(\"abc\"), 2);",
+        fix: "assertEqual(len(\"abc\"), 3);",
+    },
+    Explanation {
+        code: "E0014",
+        title: "step limit exceeded",
+        description: "The script executed more statements and evaluated more expressions \
+            than allowed by `--max-steps` or `Limits::steps`, e.g. in an endless loop.",
+        example: "while (true) {}",
+        fix: "var i = 0;\nwhile (i < 10) i = i + 1;",
+    },
+    Explanation {
+        code: "E0015",
+        title: "time limit exceeded",
+        description: "The script ran for longer than allowed by `--max-time` or `Limits::time`, \
+            counted from the start of the interpreter. `sleep` stops at the limit.",
+        example: "sleep(60);\nprint \"awake\";",
+        fix: "sleep(1);",
+    },
+    Explanation {
+        code: "E0016",
+        title: "call depth limit exceeded",
+        description: "More calls and imports were running at once than allowed by `--max-depth` \
+            (10000 by default) or `Limits::depth` (100 by default), usually a recursion without an end.",
+        example: "fun f() { f(); }\nf();",
+        fix: "fun f(n) { if (n > 0) f(n - 1); }\nf(10);",
+    },
+    Explanation {
+        code: "E0017",
+        title: "string memory limit exceeded",
+        description: "The strings made by concatenations and natives add up to more bytes than \
+            allowed by `--max-string-bytes` or `Limits::string_bytes`, whether they're still \
+            used or not.",
+        example: "var s = \"a\";\nwhile (true) s = s + s;",
+        fix: "var s = repeat(\"a\", 100);",
+    },
+    Explanation {
+        code: "E0018",
+        title: "permission denied",
+        description: "The interpreter wasn't allowed the capability a native needs, e.g. with \
+            `--sandbox` or another `--allow-` option on the command line, or \
+            `Interpreter::builder()` when embedding, or the file read, written or imported is \
+            outside the directories given to `--allow-read=` or `--allow-write=`, or a link \
+            to a missing file in them.",
+        example: "// yayarlox --sandbox script.lox\nprint getenv(\"HOME\");",
+        fix: "// yayarlox --allow-env script.lox\nprint getenv(\"HOME\");",
+    },
+    Explanation {
+        code: "E0019",
+        title: "invalid import path",
+        description: "The path of an import is empty, so it names no file.",
+        example: "import \"\";",
+        fix: "import \"lib.lox\";",
+    },
+    Explanation {
+        code: "E0020",
+        title: "import failed",
+        description: "The file imported couldn't be found or read. Its path is relative to \
+            the directory of the file importing it, and the message gives the reason \
+            reported by the system.",
+        example: "// main.lox, next to lib/util.lox\nimport \"util.lox\";",
+        fix: "// main.lox, next to lib/util.lox\nimport \"lib/util.lox\";",
+    },
+    Explanation {
+        code: "W0001",
+        title: "unused variable",
+        description: "A local variable is declared but never used. \
+            Names starting with an underscore aren't reported.",
+        example: "fun f() {\n    var unused = 1;\n}",
+        fix: "fun f() {}",
+    },
+    Explanation {
+        code: "W0002",
+        title: "unused parameter",
+        description: "A parameter is never used in the function's body. \
+            Names starting with an underscore aren't reported.",
+        example: "fun greet(name) {\n    print \"Hello\";\n}",
+        fix: "fun greet(name) {\n    print \"Hello \" + name;\n}",
+    },
+    Explanation {
+        code: "W0003",
+        title: "shadowing",
+        description: "A declaration hides one of the same name from an outer scope, \
+            which can't be used in the inner scope anymore.",
+        example: "var total = 0;\nfun add(n) {\n    var total = n;\n}",
+        fix: "var total = 0;\nfun add(n) {\n    total = total + n;\n}",
+    },
+    Explanation {
+        code: "W0004",
+        title: "unreachable code",
+        description: "The statement follows a call to `exit` or a `throw`, so it never runs.",
+        example: "exit(1);\nprint \"done\";",
+        fix: "print \"done\";\nexit(1);",
+    },
+    Explanation {
+        code: "W0005",
+        title: "expression without effect",
+        description: "The expression statement computes a value which is thrown away, \
+            without calling anything or assigning to a variable.",
+        example: "a == 1;",
+        fix: "a = 1;",
+    },
+    Explanation {
+        code: "W0006",
+        title: "assignment as a condition",
+        description: "The condition of an `if` assigns to a variable, \
+            a comparison with `==` was likely meant.",
+        example: "if (a = 1) print a;",
+        fix: "if (a == 1) print a;",
+    },
+    Explanation {
+        code: "W0007",
+        title: "wrong number of arguments",
+        description: "A call to a known function passes a different number of arguments \
+            than it has parameters, which fails at runtime (E0003).",
+        example: "fun add(a, b) { print a + b; }\nadd(1);",
+        fix: "fun add(a, b) { print a + b; }\nadd(1, 2);",
+    },
+    Explanation {
+        code: "W0008",
+        title: "global declared again",
+        description: "A global variable is declared a second time, replacing the first one. \
+            Assigning to it was likely meant. Also reported when running a script.",
+        example: "var count = 0;\nvar count = 1;",
+        fix: "var count = 0;\ncount = 1;",
+    },
+    Explanation {
+        code: "W0009",
+        title: "comparison of different types",
+        description: "Values of different types are never equal, so the comparison is always \
+            false with `==` and true with `!=`. Comparing with nil isn't reported. \
+            Only checked when running, the first time the comparison is evaluated.",
+        example: "var input = \"1\";\nif (input == 1) print \"one\";",
+        fix: "var input = \"1\";\nif (input == \"1\") print \"one\";",
+    },
+    Explanation {
+        code: "W0010",
+        title: "division by zero",
+        description: "The divisor is a literal zero, so the result is infinite, or NaN for 0 / 0. \
+            Also reported when running a script.",
+        example: "var average = total / 0;",
+        fix: "var average = total / count;",
+    },
+    Explanation {
+        code: "W0011",
+        title: "unknown lint",
+        description: "A `//!` pragma names a lint that doesn't exist. \
+            Lints are named by their code or name, e.g. `W0003` or `shadowing`.",
+        example: "//! allow(shadow)",
+        fix: "//! allow(shadowing)",
+    },
+];
+
+pub fn find(code: &str) -> Option<&'static Explanation> {
+    CATALOGUE
+        .iter()
+        .find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+}
+
+/// The explanation as printed, with its examples indented
+pub fn render(explanation: &Explanation) -> String {
+    let mut result = format!(
+        "{}: {}\n\n{}\n",
+        explanation.code, explanation.title, explanation.description
+    );
+
+    if !explanation.example.is_empty() {
+        result.push_str(&format!(
+            "\nErroneous code example:\n\n{}\n",
+            indent(explanation.example)
+        ));
+    }
+
+    result.push_str(&format!("\nFix:\n\n{}\n", indent(explanation.fix)));
+
+    result
+}
+
+fn indent(code: &str) -> String {
+    code.lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
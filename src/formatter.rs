@@ -0,0 +1,299 @@
+/// Opinionated source formatter
+///
+/// Works on the token stream rather than the AST, so that comments
+/// and the author's line breaks survive. Line breaks are kept, except:
+/// - statements each go on their own line
+/// - opening braces stay on the line of the `)` or `else` preceding them
+/// - empty braces are written `{}`
+/// - there is at most one blank line in a row, none after `{` nor before `}`
+///
+/// Spacing within lines and indentation are entirely rewritten.
+use crate::lexer::{self, Token, TokenVariant};
+
+const INDENT: &str = "    ";
+
+/// Returns None if the code couldn't be lexed, errors being already reported
+pub fn format(code: String, source: usize) -> Option<String> {
+    let (tokens, had_error) = lexer::scan_with_trivia(code, source);
+
+    if had_error {
+        return None;
+    }
+
+    let mut state = Formatter {
+        output: String::new(),
+        line: String::new(),
+
+        depth: 0,
+        parens: Vec::new(),
+        hangs: Vec::new(),
+        ifs: Vec::new(),
+        pending_body: false,
+        mid_statement: false,
+        unary: false,
+    };
+
+    let mut previous: Option<&Token> = None;
+    // line breaks met since the last token that isn't a line break
+    let mut newlines = 0;
+
+    for token in tokens.iter() {
+        match token.class {
+            TokenVariant::Newline => {
+                newlines += 1;
+                continue;
+            }
+            TokenVariant::Eof => break,
+            _ => (),
+        }
+
+        match previous {
+            None => (),
+            Some(prev) => {
+                if state.breaks_after(prev, token, newlines) {
+                    state.end_line();
+
+                    if newlines >= 2 && keeps_blank_line(prev, token) {
+                        state.output.push('\n');
+                    }
+                } else if spaced(prev, token, state.unary) {
+                    state.line.push(' ');
+                }
+            }
+        }
+
+        state.push(token, previous);
+
+        previous = Some(token);
+        newlines = 0;
+    }
+
+    state.end_line();
+
+    Some(state.output)
+}
+
+struct Formatter {
+    output: String,
+    // line being built, without indentation
+    line: String,
+
+    // number of braces opened
+    depth: usize,
+    // for each opened parenthesis, whether it holds the header
+    // of a control flow statement, `if (...)`
+    parens: Vec<bool>,
+    // brace depth of the bodies without braces being laid out,
+    // `if (x)\n    print x;`
+    hangs: Vec<usize>,
+    // brace depth and hangs of the `if`s which may still get an `else`,
+    // which goes at the level of its `if`
+    ifs: Vec<(usize, Vec<usize>)>,
+
+    // a control flow header just ended, its body comes next
+    pending_body: bool,
+    // a statement started and hasn't ended yet
+    mid_statement: bool,
+    // the last token is a unary operator
+    unary: bool,
+}
+
+impl Formatter {
+    /// Whether a line break goes between two tokens,
+    /// `newlines` being the number of those found in the source
+    fn breaks_after(&self, prev: &Token, next: &Token, newlines: usize) -> bool {
+        match (&prev.class, &next.class) {
+            // trailing comments stay where they are
+            (_, TokenVariant::Comment) if newlines == 0 => false,
+            (TokenVariant::Comment, _) => true,
+
+            (TokenVariant::LeftBrace, TokenVariant::RightBrace) => false,
+            (TokenVariant::LeftBrace, _) | (_, TokenVariant::RightBrace) => true,
+
+            (TokenVariant::RightParen, TokenVariant::LeftBrace)
+            | (TokenVariant::Else, TokenVariant::LeftBrace)
+            | (TokenVariant::Try, TokenVariant::LeftBrace)
+            | (TokenVariant::Finally, TokenVariant::LeftBrace) => false,
+
+            (TokenVariant::RightBrace, TokenVariant::Else)
+            | (TokenVariant::RightBrace, TokenVariant::Catch)
+            | (TokenVariant::RightBrace, TokenVariant::Finally) => false,
+            (TokenVariant::RightBrace, _) => true,
+
+            (TokenVariant::Semicolon, _) if self.parens.is_empty() => true,
+
+            _ => newlines > 0,
+        }
+    }
+
+    fn push(&mut self, token: &Token, previous: Option<&Token>) {
+        let starts_line = self.line.is_empty();
+
+        self.unary = match token.class {
+            TokenVariant::Bang => true,
+            TokenVariant::Minus => !previous.is_some_and(ends_operand),
+            _ => false,
+        };
+
+        match token.class {
+            TokenVariant::Comment => {
+                if starts_line {
+                    self.indent(self.depth + self.hangs.len());
+                }
+                self.line.push_str(token.lexeme.trim_end());
+                return;
+            }
+
+            TokenVariant::LeftBrace => {
+                // a brace right after a header is the body itself
+                self.pending_body = false;
+            }
+
+            TokenVariant::RightBrace => {
+                self.depth = self.depth.saturating_sub(1);
+
+                let depth = self.depth;
+                self.ifs.retain(|(if_depth, _)| *if_depth <= depth);
+            }
+
+            // `else if` chains stay at the level of their first `if`
+            TokenVariant::If if previous.is_some_and(|prev| prev.class == TokenVariant::Else) => {
+                self.pending_body = false;
+                self.ifs.push((self.depth, self.hangs.clone()));
+            }
+
+            TokenVariant::If => {
+                if self.pending_body {
+                    self.hangs.push(self.depth);
+                    self.pending_body = false;
+                }
+                self.ifs.push((self.depth, self.hangs.clone()));
+            }
+
+            // the closest `if` without one
+            TokenVariant::Else => {
+                let depth = self.depth;
+                if let Some(position) = self
+                    .ifs
+                    .iter()
+                    .rposition(|(if_depth, _)| *if_depth == depth)
+                {
+                    self.hangs = self.ifs.remove(position).1;
+                }
+            }
+
+            _ => {
+                if self.pending_body {
+                    self.hangs.push(self.depth);
+                    self.pending_body = false;
+                }
+            }
+        }
+
+        if starts_line {
+            let continued = self.mid_statement || !self.parens.is_empty();
+            self.indent(self.depth + self.hangs.len() + continued as usize);
+        }
+
+        self.line.push_str(&token.lexeme);
+
+        match token.class {
+            TokenVariant::LeftParen => {
+                let header = matches!(
+                    previous.map(|prev| &prev.class),
+                    Some(TokenVariant::If) | Some(TokenVariant::While) | Some(TokenVariant::For)
+                );
+                self.parens.push(header);
+                self.mid_statement = true;
+            }
+
+            TokenVariant::RightParen => {
+                if self.parens.pop() == Some(true) {
+                    self.pending_body = true;
+                    self.mid_statement = false;
+                }
+            }
+
+            TokenVariant::LeftBrace => {
+                self.depth += 1;
+                self.mid_statement = false;
+            }
+
+            TokenVariant::RightBrace => {
+                self.end_statement();
+            }
+
+            TokenVariant::Semicolon if self.parens.is_empty() => {
+                self.end_statement();
+            }
+
+            TokenVariant::Else => {
+                self.pending_body = true;
+                self.mid_statement = false;
+            }
+
+            _ => self.mid_statement = true,
+        }
+    }
+
+    fn end_statement(&mut self) {
+        self.mid_statement = false;
+
+        while self.hangs.last() == Some(&self.depth) {
+            self.hangs.pop();
+        }
+    }
+
+    fn indent(&mut self, level: usize) {
+        self.line.push_str(&INDENT.repeat(level));
+    }
+
+    fn end_line(&mut self) {
+        if !self.line.is_empty() {
+            self.output.push_str(&self.line);
+            self.output.push('\n');
+            self.line.clear();
+        }
+    }
+}
+
+fn keeps_blank_line(prev: &Token, next: &Token) -> bool {
+    prev.class != TokenVariant::LeftBrace && next.class != TokenVariant::RightBrace
+}
+
+/// Whether a space separates two tokens on the same line,
+/// `unary` telling if `prev` is a unary operator
+fn spaced(prev: &Token, next: &Token, unary: bool) -> bool {
+    match (&prev.class, &next.class) {
+        (_, TokenVariant::Comment) => true,
+
+        (TokenVariant::LeftParen, _) | (TokenVariant::Dot, _) => false,
+        (TokenVariant::LeftBrace, TokenVariant::RightBrace) => false,
+        (_, TokenVariant::RightParen)
+        | (_, TokenVariant::Comma)
+        | (_, TokenVariant::Semicolon)
+        | (_, TokenVariant::Dot) => false,
+
+        // calls
+        (TokenVariant::Identifier(_), TokenVariant::LeftParen)
+        | (TokenVariant::RightParen, TokenVariant::LeftParen) => false,
+
+        _ => !unary,
+    }
+}
+
+/// Whether an operator following this token would be binary
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token.class,
+        TokenVariant::Identifier(_)
+            | TokenVariant::String(_)
+            | TokenVariant::Number(_)
+            | TokenVariant::RightParen
+            | TokenVariant::True
+            | TokenVariant::False
+            | TokenVariant::Nil
+            | TokenVariant::This
+            | TokenVariant::Super
+    )
+}
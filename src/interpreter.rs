@@ -1,11 +1,21 @@
 use crate::ast::*;
-use crate::errors;
-use crate::lexer::{Token, TokenVariant};
-use std::{
-    collections::HashMap,
-    fmt, ptr,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use crate::capabilities::{Builder, Capability, Denied, Permissions};
+use crate::debugger::{self, Debugger};
+use crate::errors::{self, Diagnostic, Severity};
+use crate::levels::Levels;
+use crate::lexer::{Span, Token, TokenVariant, KEYWORDS};
+use crate::limits::Limits;
+use crate::lint::{self, TYPE_MISMATCH};
+use crate::natives::*;
+use crate::Program;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+use std::{fmt, fs, mem, ptr};
 
 #[allow(non_camel_case_types)]
 #[derive(Clone, PartialEq)]
@@ -14,11 +24,30 @@ pub enum types {
     boolean(bool),
     number(f64),
     string(String),
+    list(Vec<types>),
+    error(ErrorValue),
+    module(Rc<Module>),
 
     native_function(Box<dyn Callable>),
     function(Function),
 }
 
+impl types {
+    /// Name of the value's type, as shown in errors
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            types::nil => "nil",
+            types::boolean(_) => "boolean",
+            types::number(_) => "number",
+            types::string(_) => "string",
+            types::list(_) => "list",
+            types::error(_) => "error",
+            types::module(_) => "module",
+            types::native_function(_) | types::function(_) => "function",
+        }
+    }
+}
+
 impl fmt::Display for types {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -29,6 +58,9 @@ impl fmt::Display for types {
                 types::boolean(val) => val.to_string(),
                 types::number(val) => val.to_string(),
                 types::string(val) => val.to_string(),
+                types::list(values) => format_list(values),
+                types::error(error) => error.message.clone(),
+                types::module(module) => format!("<module {}>", module.name),
 
                 types::native_function(_) => String::from("<native fn>"),
                 types::function(_) => String::from("<fn>"),
@@ -44,10 +76,13 @@ impl fmt::Debug for types {
             "{}",
             match &self {
                 types::nil => String::from("nil"),
-                types::string(val) => format!("\"{}\"", val.to_string()),
+                types::string(val) => format!("\"{}\"", val),
 
                 types::boolean(val) => val.to_string(),
                 types::number(val) => val.to_string(),
+                types::list(values) => format_list(values),
+                types::error(error) => format!("<error: {}>", error.message),
+                types::module(module) => format!("<module {}>", module.name),
 
                 types::native_function(_) => String::from("<native fn>"),
                 types::function(_) => String::from("<function>"),
@@ -56,66 +91,187 @@ impl fmt::Debug for types {
     }
 }
 
-/// Returns the numbers of seconds since UNIX EPOCH
-#[derive(Clone)]
-struct NativeClock;
+/// Elements are shown as the REPL would show them,
+/// so strings keep their quotes
+fn format_list(values: &[types]) -> String {
+    let elements: Vec<String> = values.iter().map(|value| format!("{:?}", value)).collect();
 
-impl Callable for NativeClock {
-    fn arity(&self) -> u8 {
-        0
+    format!("[{}]", elements.join(", "))
+}
+
+/// Ways the execution of statements can be cut short
+pub enum Interrupt {
+    /// A runtime error, reported once it reaches the top
+    Error(Box<RuntimeError>),
+    /// The script asked to end the process with this status
+    Exit(i32),
+}
+
+pub struct RuntimeError {
+    pub diagnostic: Diagnostic,
+    // calls the error went through, innermost first
+    pub trace: Vec<Frame>,
+    // set for errors raised by `throw`
+    pub thrown: Option<types>,
+}
+
+impl RuntimeError {
+    /// The interrupt carrying a new error
+    pub fn interrupt(diagnostic: Diagnostic) -> Interrupt {
+        Interrupt::Error(Box::new(RuntimeError {
+            diagnostic,
+            trace: Vec::new(),
+            thrown: None,
+        }))
     }
 
-    fn call(&self, _interpreter: &mut Interpreter, _arguments: Vec<Expr>) -> Result<types, ()> {
-        match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(n) => Ok(types::number(n.as_secs() as f64)),
-            Err(_) => panic!("SystemTime before UNIX EPOCH!"),
+    /// The value a `catch` receives: the one thrown,
+    /// or an error value for errors of the interpreter
+    pub fn into_value(self) -> types {
+        match self.thrown {
+            Some(value) => value,
+            None => types::error(ErrorValue {
+                message: self.diagnostic.message,
+                line: self.diagnostic.line,
+            }),
         }
     }
+
+    /// Where execution was at, innermost first,
+    /// e.g. `in factorial() [line 62]` then `in script [line 75]`.
+    /// Deep ones keep only their ends, e.g. after a runaway recursion.
+    pub fn traceback(&self) -> Vec<String> {
+        let names = self
+            .trace
+            .iter()
+            .map(|frame| format!("in {}", frame.function))
+            .chain(std::iter::once(String::from("in script")));
+
+        let lines =
+            std::iter::once(self.diagnostic.line).chain(self.trace.iter().map(|frame| frame.line));
+
+        let mut traceback: Vec<String> = names
+            .zip(lines)
+            .map(|(name, line)| format!("{} [line {}]", name, line))
+            .collect();
+
+        if traceback.len() > 2 * TRACEBACK_ENDS {
+            let hidden = traceback.len() - 2 * TRACEBACK_ENDS;
+
+            traceback.splice(
+                TRACEBACK_ENDS..traceback.len() - TRACEBACK_ENDS,
+                std::iter::once(format!("... {} more", hidden)),
+            );
+        }
+
+        traceback
+    }
+
+    /// Prints the error followed by its traceback
+    pub fn report(&self) {
+        let mut diagnostic = self.diagnostic.clone();
+        diagnostic.notes.extend(self.traceback());
+
+        errors::emit(diagnostic);
+    }
+}
+
+// frames shown at each end of a long traceback
+const TRACEBACK_ENDS: usize = 10;
+
+/// A runtime error once caught, or made by `error(message)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorValue {
+    pub message: String,
+    pub line: usize,
+}
+
+/// A function call, or an import, being executed
+#[derive(Clone)]
+pub struct Frame {
+    // e.g. `factorial()`, or the path of the file imported
+    pub function: String,
+    // where the call was made
+    pub line: usize,
 }
 
 #[derive(Clone)]
 pub struct Function {
-    declaration: Stmt,
+    name: Token,
+    params: Vec<Token>,
+    body: Vec<Stmt>,
+    // where it was declared, None for the script
+    module: Option<Rc<Module>>,
 }
 
 impl Callable for Function {
     fn arity(&self) -> u8 {
-        if let Stmt::Function(_, params, _) = &self.declaration {
-            params.len() as u8
-        } else {
-            panic!("declaration should be Stmt::Function");
-        }
+        self.params.len() as u8
     }
 
-    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Expr>) -> Result<types, ()> {
-        if let Stmt::Function(_, params, body) = &self.declaration {
-            let mut new_scope = HashMap::new();
-
-            for (param, argument) in params.clone().into_iter().zip(arguments.into_iter()) {
-                new_scope.insert(param.lexeme.clone(), interpreter.evaluate(argument)?);
-            }
-
-            interpreter.environment = Environment {
-                enclosing: Some(Box::new(std::mem::replace(
-                    &mut interpreter.environment,
-                    Environment {
-                        enclosing: None,
-                        scope: HashMap::new(),
-                    },
-                ))),
-                scope: new_scope,
-            };
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<types>,
+        paren: &Token,
+    ) -> Result<types, Interrupt> {
+        let mut new_scope = HashMap::new();
 
-            interpreter.execution_bubble((*body).to_vec())?;
+        for (param, argument) in self.params.iter().zip(arguments) {
+            new_scope.insert(param.lexeme.clone(), argument);
+        }
 
-            let current = interpreter.environment.enclosing.take().unwrap();
-            interpreter.environment = *current;
+        interpreter.push_frame(
+            Frame {
+                function: format!("{}()", self.name.lexeme),
+                line: paren.line,
+            },
+            paren,
+        )?;
+
+        // its body sees the globals of its own file
+        let mut result = interpreter.in_module(self.module.clone(), |interpreter| {
+            interpreter.in_scope(new_scope, |interpreter| {
+                interpreter.execution_bubble(self.body.clone())
+            })
+        });
+
+        let frame = interpreter.call_stack.pop();
+
+        if let (Err(Interrupt::Error(error)), Some(frame)) = (&mut result, frame) {
+            error.trace.push(frame);
         }
+        result?;
 
         Ok(types::nil)
     }
 }
 
+/// Body of a native defined by the host, failing with an error message
+pub type HostFunction = dyn Fn(&[types]) -> Result<types, String>;
+
+/// A native defined by the host with `Interpreter::define_native`
+#[derive(Clone)]
+struct Closure {
+    arity: u8,
+    function: Rc<HostFunction>,
+}
+
+impl Callable for Closure {
+    fn arity(&self) -> u8 {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: Vec<types>,
+        paren: &Token,
+    ) -> Result<types, Interrupt> {
+        (self.function)(&arguments).map_err(|message| error(paren, "E0005", &message))
+    }
+}
+
 pub trait CloneUnsizedCallable {
     fn clone_unsized_box(&self) -> Box<dyn Callable>;
 }
@@ -129,7 +285,14 @@ impl<T: Callable + Clone + 'static> CloneUnsizedCallable for T {
 pub trait Callable: CloneUnsizedCallable {
     fn arity(&self) -> u8;
 
-    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Expr>) -> Result<types, ()>;
+    /// Arguments are already evaluated, `paren` is the closing
+    /// parenthesis of the call, where errors should be reported
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<types>,
+        paren: &Token,
+    ) -> Result<types, Interrupt>;
 }
 
 impl Clone for Box<dyn Callable> {
@@ -157,6 +320,30 @@ impl PartialEq for Function {
     }
 }
 
+/// A file run by `import`, with the global variables it left
+pub struct Module {
+    // path of the file, from the current directory if relative
+    pub name: String,
+    // globals of the module, empty while its code runs as they're
+    // then the global scope of the interpreter
+    globals: RefCell<HashMap<String, types>>,
+    // levels of the warnings reported running its code
+    levels: Levels,
+}
+
+impl Module {
+    /// Value of a global variable of the module
+    pub fn get(&self, name: &str) -> Option<types> {
+        self.globals.borrow().get(name).cloned()
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
 struct Environment {
     // leads to the enclosing Environment, or is None if
     // it is the global scope
@@ -180,54 +367,273 @@ impl Environment {
         self.scope.insert(name, initializer);
     }
 
-    fn assign(&mut self, name: Token, value: types) -> Result<types, ()> {
-        if self.scope.contains_key(&name.lexeme) {
-            self.scope.insert(name.lexeme, value.clone());
-            Ok(value)
+    /// None if the variable isn't defined
+    fn assign(&mut self, name: &str, value: types) -> Option<types> {
+        if let Some(slot) = self.scope.get_mut(name) {
+            *slot = value.clone();
+            Some(value)
         } else if let Some(env) = &mut self.enclosing {
             // recursion => access to all parent scopes
             (*env).assign(name, value)
         } else {
-            error(&name, &format!("Undefined variable '{}'.", &name.lexeme));
-            Err(())
+            None
         }
     }
 
-    fn get(&self, name: &str) -> Result<types, ()> {
+    /// Names of every scope, innermost first
+    fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.scope.keys().map(String::as_str).collect();
+
+        if let Some(env) = &self.enclosing {
+            names.extend(env.names());
+        }
+
+        names
+    }
+
+    /// ", did you mean 'x'?" when a visible name or a keyword
+    /// is close to the undefined `name`, or nothing
+    fn suggestion(&self, name: &str) -> String {
+        let candidates = self.names().into_iter().chain(KEYWORDS);
+
+        match errors::closest(name, candidates) {
+            Some(closest) => format!(", did you mean '{}'?", closest),
+            None => String::from("."),
+        }
+    }
+
+    fn global(&self) -> &Environment {
+        match &self.enclosing {
+            Some(env) => env.global(),
+            None => self,
+        }
+    }
+
+    fn global_mut(&mut self) -> &mut Environment {
+        match self.enclosing {
+            Some(ref mut env) => env.global_mut(),
+            None => self,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<types> {
         match self.scope.get(name) {
-            Some(val) => Ok((*val).clone()),
-            None => {
-                if let Some(env) = &self.enclosing {
-                    (*env).get(name)
-                } else {
-                    Err(())
-                }
-            }
+            Some(val) => Some((*val).clone()),
+            None => self.enclosing.as_ref().and_then(|env| env.get(name)),
         }
     }
 }
 
 pub struct Interpreter {
     environment: Environment,
+
+    // extra command-line arguments, given to the script by `args()`
+    pub(crate) script_args: Vec<String>,
+
+    // innermost call last
+    pub(crate) call_stack: Vec<Frame>,
+    // only set when running under the debugger
+    pub(crate) debugger: Option<Debugger>,
+
+    // levels of the warnings reported while running
+    pub(crate) levels: Levels,
+    // what the script may use, and how much it did
+    pub limits: Limits,
+    permissions: Permissions,
+    // counted from the start of each run, by `interpret` or `call`
+    steps: u64,
+    string_bytes: usize,
+    pub(crate) ran: Instant,
+    // used by `random()` and `randomInt(a, b)`
+    pub(crate) random: Random,
+    // what `now()` counts from
+    pub(crate) started: Instant,
+    // spans of the operators already warned about, to warn once
+    warned: HashSet<(usize, usize, usize)>,
+
+    // natives and constants, which every module starts with
+    builtins: HashMap<String, types>,
+    // modules already run, by canonical path
+    modules: HashMap<PathBuf, Rc<Module>>,
+    // canonical and shown paths of the files running, the script
+    // first, to resolve imports and detect cycles
+    importing: Vec<(PathBuf, String)>,
+    // module whose globals are in the environment, None for the script
+    module: Option<Rc<Module>>,
+    // globals of the script while a module's are in the environment
+    script_globals: HashMap<String, types>,
 }
 
 impl Interpreter {
-    pub fn new() -> Self {
-        let mut global = Environment::new();
+    /// An interpreter allowed every capability
+    pub fn new(script_args: Vec<String>) -> Self {
+        Interpreter::with_permissions(script_args, Permissions::all())
+    }
 
-        global.define(
-            String::from("clock"),
-            types::native_function(Box::new(NativeClock)),
-        );
+    /// To allow only some capabilities, e.g. for untrusted scripts
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// An interpreter whose natives needing a capability not
+    /// allowed fail with a permission error
+    pub fn with_permissions(script_args: Vec<String>, permissions: Permissions) -> Self {
+        let mut builtins = HashMap::new();
+
+        for (name, mut native) in natives() {
+            let capability = Capability::ALL
+                .iter()
+                .copied()
+                .find(|capability| capability.natives().contains(&name));
+
+            if let Some(capability) = capability.filter(|c| !permissions.allows(*c)) {
+                native = Box::new(Denied {
+                    name,
+                    capability,
+                    arity: native.arity(),
+                });
+            }
+
+            builtins.insert(String::from(name), types::native_function(native));
+        }
+
+        for (name, value) in constants() {
+            builtins.insert(String::from(name), value);
+        }
 
         Interpreter {
-            environment: global,
+            environment: Environment {
+                enclosing: None,
+                scope: builtins.clone(),
+            },
+            script_args,
+            call_stack: Vec::new(),
+            debugger: None,
+            levels: Levels::default(),
+            limits: Limits::default(),
+            permissions,
+            steps: 0,
+            string_bytes: 0,
+            random: Random::default(),
+            started: Instant::now(),
+            ran: Instant::now(),
+            warned: HashSet::new(),
+            builtins,
+            modules: HashMap::new(),
+            importing: Vec::new(),
+            module: None,
+            script_globals: HashMap::new(),
         }
     }
 
-    pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<types, ()> {
+    /// Sets the file of the script, which its imports are relative to.
+    /// Without one, they're relative to the current directory.
+    pub fn set_path(&mut self, path: &Path) {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        self.importing = vec![(canonical, path.display().to_string())];
+    }
+
+    pub fn permissions(&self) -> &Permissions {
+        &self.permissions
+    }
+
+    /// Names defined in the global scope, natives included
+    pub fn global_names(&self) -> Vec<String> {
+        self.environment.global().scope.keys().cloned().collect()
+    }
+
+    /// Defines a global variable, or replaces its value
+    pub fn set_global(&mut self, name: &str, value: types) {
+        self.environment
+            .global_mut()
+            .define(String::from(name), value);
+    }
+
+    /// None if the global variable isn't defined
+    pub fn get_global(&self, name: &str) -> Option<types> {
+        self.environment.global().scope.get(name).cloned()
+    }
+
+    /// Defines a native calling a Rust closure with the evaluated arguments,
+    /// an error being reported as a runtime error at the call, e.g.
+    /// `interpreter.define_native("double", 1, |args| match args[0] { ... })`
+    pub fn define_native<F>(&mut self, name: &str, arity: u8, function: F)
+    where
+        F: Fn(&[types]) -> Result<types, String> + 'static,
+    {
+        let native = types::native_function(Box::new(Closure {
+            arity,
+            function: Rc::new(function),
+        }));
+
+        // modules imported afterwards see it too
+        self.builtins.insert(String::from(name), native.clone());
+        self.set_global(name, native);
+    }
+
+    /// Calls a function of a script, or a native, from Rust.
+    /// Errors of the call itself are located at line 0.
+    pub fn call(&mut self, function: types, arguments: Vec<types>) -> Result<types, Interrupt> {
+        let host = Token::new(
+            TokenVariant::Identifier(String::from("<host>")),
+            String::from("<host>"),
+            0,
+            0,
+            Span {
+                source: errors::NO_SOURCE,
+                start: 0,
+                end: 0,
+            },
+        );
+
+        self.start_run();
+
+        self.call_value(function, arguments, &host)
+    }
+
+    /// Gives the limits counted per run a fresh start, unless running already
+    fn start_run(&mut self) {
+        if self.call_stack.is_empty() {
+            self.steps = 0;
+            self.string_bytes = 0;
+            self.ran = Instant::now();
+        }
+    }
+
+    /// Variables of every scope, innermost first
+    pub(crate) fn scopes(&self) -> Vec<Vec<(String, types)>> {
+        let mut scopes = Vec::new();
+        let mut environment = Some(&self.environment);
+
+        while let Some(current) = environment {
+            let mut scope: Vec<(String, types)> = current
+                .scope
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            scope.sort_by(|a, b| a.0.cmp(&b.0));
+
+            scopes.push(scope);
+            environment = current.enclosing.as_deref();
+        }
+
+        scopes
+    }
+
+    /// Runs a program, returning the value of its last statement
+    pub fn interpret(&mut self, program: Program) -> Result<types, Interrupt> {
+        self.interpret_statements(program.statements)
+    }
+
+    pub(crate) fn interpret_statements(
+        &mut self,
+        statements: Vec<Stmt>,
+    ) -> Result<types, Interrupt> {
         let mut last = types::nil;
 
+        self.start_run();
+
         for stmt in statements.into_iter() {
             last = self.execute(stmt)?;
         }
@@ -237,344 +643,807 @@ impl Interpreter {
 
     // Interpreting
 
-    fn execute(&mut self, stmt: Stmt) -> Result<types, ()> {
+    fn execute(&mut self, stmt: Stmt) -> Result<types, Interrupt> {
+        // limits are checked by the expressions, as every statement
+        // running indefinitely evaluates some
+        self.steps += 1;
+
+        if self.debugger.is_some() {
+            debugger::before(self, &stmt)?;
+        }
+
         match stmt {
-            Stmt::Block(_) => self.execute_block(stmt),
-            Stmt::Expression(_) => self.execute_expr(stmt),
-            Stmt::Function(_, _, _) => self.execute_function(stmt),
-            Stmt::If(_, _, _) => self.execute_if(stmt),
-            Stmt::Print(_) => self.execute_print(stmt),
-            Stmt::Var(_, _) => self.execute_var(stmt),
-            Stmt::While(_, _) => self.execute_while(stmt),
+            Stmt::Block(statements) => self.execute_block(*statements),
+            // the debugger already paused if there's one
+            Stmt::Debugger(_) => Ok(types::nil),
+            Stmt::Expression(expr) => self.evaluate(*expr),
+            Stmt::Function(name, params, body) => self.execute_function(*name, *params, *body),
+            Stmt::If(condition, then_branch, else_branch) => {
+                self.execute_if(*condition, *then_branch, *else_branch)
+            }
+            Stmt::Import(keyword, path, name) => self.execute_import(*keyword, *path, *name),
+            Stmt::Print(expr) => self.execute_print(*expr),
+            // only run by `yayarlox test`
+            Stmt::Test(_, _, _) => Ok(types::nil),
+            Stmt::Throw(keyword, value) => self.execute_throw(*keyword, *value),
+            Stmt::Try(_, body, name, handler, finally) => {
+                self.execute_try(*body, *name, *handler, *finally)
+            }
+            Stmt::Var(name, initializer) => self.execute_var(*name, *initializer),
+            Stmt::While(condition, body) => self.execute_while(*condition, *body),
         }
     }
 
-    fn execute_block(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::Block(statements) = stmt {
-            self.environment = Environment {
-                enclosing: Some(Box::new(std::mem::replace(
-                    &mut self.environment,
-                    Environment {
-                        enclosing: None,
-                        scope: HashMap::new(),
-                    },
-                ))),
-                scope: HashMap::new(),
-            };
+    fn execute_block(&mut self, statements: Vec<Stmt>) -> Result<types, Interrupt> {
+        self.in_scope(HashMap::new(), |interpreter| {
+            interpreter.execution_bubble(statements)
+        })
+    }
 
-            self.execution_bubble(*statements)?;
+    /// Runs `f` in a new scope enclosed by the current one, which is
+    /// restored afterwards whether `f` succeeded or not
+    fn in_scope<T>(
+        &mut self,
+        scope: HashMap<String, types>,
+        f: impl FnOnce(&mut Self) -> Result<T, Interrupt>,
+    ) -> Result<T, Interrupt> {
+        let enclosing = std::mem::replace(&mut self.environment, Environment::new());
+
+        self.environment = Environment {
+            enclosing: Some(Box::new(enclosing)),
+            scope,
+        };
+
+        let result = f(self);
+
+        // nested scopes were restored the same way, so this is the one set above
+        if let Some(enclosing) = self.environment.enclosing.take() {
+            self.environment = *enclosing;
+        }
 
-            let current = self.environment.enclosing.take().unwrap();
-            self.environment = *current;
+        result
+    }
+
+    /// Runs `f` with the globals of `module` (None for the script's)
+    /// as the global scope, without the scopes of the current code.
+    /// Both are restored afterwards, whether `f` succeeded or not.
+    fn in_module<T>(
+        &mut self,
+        module: Option<Rc<Module>>,
+        f: impl FnOnce(&mut Self) -> Result<T, Interrupt>,
+    ) -> Result<T, Interrupt> {
+        let same = match (&self.module, &module) {
+            (Some(current), Some(module)) => Rc::ptr_eq(current, module),
+            (current, module) => current.is_none() && module.is_none(),
+        };
+
+        if same {
+            return f(self);
         }
 
-        Ok(types::nil)
+        let mut saved = mem::replace(&mut self.environment, Environment::new());
+        let current = mem::replace(&mut self.module, module);
+
+        self.store_globals(&current, mem::take(&mut saved.global_mut().scope));
+        self.environment.scope = self.take_globals(&self.module.clone());
+
+        let result = f(self);
+
+        let module = mem::replace(&mut self.module, current);
+        let globals = mem::take(&mut self.environment.scope);
+        self.store_globals(&module, globals);
+
+        saved.global_mut().scope = self.take_globals(&self.module.clone());
+        self.environment = saved;
+
+        result
     }
 
-    fn execution_bubble(&mut self, statements: Vec<Stmt>) -> Result<types, ()> {
-        for stmt in statements.into_iter() {
-            self.execute(stmt)?;
+    /// Levels of the warnings of the code running, its module's or the script's
+    fn levels(&self) -> &Levels {
+        match &self.module {
+            Some(module) => &module.levels,
+            None => &self.levels,
         }
+    }
 
-        Ok(types::nil)
+    fn store_globals(&mut self, module: &Option<Rc<Module>>, globals: HashMap<String, types>) {
+        match module {
+            Some(module) => *module.globals.borrow_mut() = globals,
+            None => self.script_globals = globals,
+        }
     }
 
-    fn execute_expr(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::Expression(expr) = stmt {
-            self.evaluate(*expr)
-        } else {
-            panic!("execute_expr expects Stmt::Expression");
+    fn take_globals(&mut self, module: &Option<Rc<Module>>) -> HashMap<String, types> {
+        match module {
+            Some(module) => mem::take(&mut *module.globals.borrow_mut()),
+            None => mem::take(&mut self.script_globals),
+        }
+    }
+
+    fn execution_bubble(&mut self, statements: Vec<Stmt>) -> Result<types, Interrupt> {
+        for stmt in statements.into_iter() {
+            self.execute(stmt)?;
         }
+
+        Ok(types::nil)
     }
 
     // Executing the Function statement, which means DEFINING the function
     // NOT executing it
-    fn execute_function(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::Function(ref name, _, _) = stmt {
-            self.environment.define(
-                name.lexeme.clone(),
-                types::function(Function { declaration: stmt }),
-            );
-        }
+    fn execute_function(
+        &mut self,
+        name: Token,
+        params: Vec<Token>,
+        body: Vec<Stmt>,
+    ) -> Result<types, Interrupt> {
+        let function = Function {
+            name: name.clone(),
+            params,
+            body,
+            module: self.module.clone(),
+        };
+
+        self.environment
+            .define(name.lexeme, types::function(function));
 
         Ok(types::nil)
     }
 
-    fn execute_if(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::If(condition, then_branch, else_branch) = stmt {
-            if is_truthy(&self.evaluate(*condition)?) {
-                self.execute(*then_branch)?;
-            } else {
-                // If no else clause were given
-                // we "execute" {}
-                self.execute(*else_branch)?;
-            }
+    fn execute_if(
+        &mut self,
+        condition: Expr,
+        then_branch: Stmt,
+        else_branch: Stmt,
+    ) -> Result<types, Interrupt> {
+        if is_truthy(&self.evaluate(condition)?) {
+            self.execute(then_branch)?;
+        } else {
+            // If no else clause were given
+            // we "execute" {}
+            self.execute(else_branch)?;
         }
 
         Ok(types::nil)
     }
 
-    fn execute_print(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::Print(expr) = stmt {
-            let value = self.evaluate(*expr)?;
-            println!("{}", value);
+    /// Defines the globals of the module imported, natives aside,
+    /// or a variable holding the module when it's given a name
+    fn execute_import(
+        &mut self,
+        keyword: Token,
+        path: Token,
+        name: Option<Token>,
+    ) -> Result<types, Interrupt> {
+        let module = self.import(&keyword, &path)?;
+
+        match name {
+            Some(name) => self.environment.define(name.lexeme, types::module(module)),
+            None => {
+                let globals = module.globals.borrow();
+
+                for (name, value) in globals.iter() {
+                    if !self.builtins.contains_key(name) {
+                        self.environment.define(name.clone(), value.clone());
+                    }
+                }
+            }
         }
 
         Ok(types::nil)
     }
 
-    fn execute_var(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::Var(name, initializer) = stmt {
-            let name = name.lexeme;
-            let initializer = self.evaluate(*initializer)?;
+    /// The module of a file, run the first time it's imported
+    fn import(&mut self, keyword: &Token, path: &Token) -> Result<Rc<Module>, Interrupt> {
+        let relative = match &path.class {
+            TokenVariant::String(relative) if !relative.is_empty() => relative,
+            _ => return Err(error(path, "E0019", "Invalid import path.")),
+        };
+
+        // relative to the directory of the file importing
+        let shown = match self.importing.last() {
+            Some((_, importer)) => Path::new(importer)
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(relative),
+            None => PathBuf::from(relative),
+        };
+        let name = shown.display().to_string();
+
+        // not even telling whether it exists otherwise
+        let checked = self.permissions.check_read(&name, path)?;
+
+        let canonical = fs::canonicalize(&checked).map_err(|reason| {
+            error(
+                path,
+                "E0020",
+                &format!("Couldn't import '{}': {}.", name, reason),
+            )
+        })?;
+
+        if let Some(module) = self.modules.get(&canonical) {
+            return Ok(module.clone());
+        }
+
+        if let Some(start) = self
+            .importing
+            .iter()
+            .position(|(file, _)| *file == canonical)
+        {
+            let cycle: Vec<&str> = self.importing[start..]
+                .iter()
+                .map(|(_, shown)| shown.as_str())
+                .chain(std::iter::once(name.as_str()))
+                .collect();
+
+            return Err(error(
+                path,
+                "E0010",
+                &format!("Import cycle: {}.", cycle.join(" -> ")),
+            ));
+        }
+
+        let code = fs::read_to_string(&canonical).map_err(|reason| {
+            error(
+                path,
+                "E0020",
+                &format!("Couldn't import '{}': {}.", name, reason),
+            )
+        })?;
+
+        // its own errors are reported as it's parsed
+        let program = crate::lex_and_parse(&name, code.clone()).map_err(|_| {
+            error(
+                path,
+                "E0011",
+                &format!("Couldn't import '{}' as it has errors.", name),
+            )
+        })?;
+
+        // with the levels of the command line and its own pragmas
+        let (levels, mut warnings) = self.levels.for_file(&code);
+        warnings.extend(lint::on_run(&code, &program.statements));
+
+        if levels.report(warnings).1 {
+            return Err(error(
+                path,
+                "E0011",
+                &format!("Couldn't import '{}' as it has errors.", name),
+            ));
+        }
+
+        let module = Rc::new(Module {
+            name: name.clone(),
+            globals: RefCell::new(self.builtins.clone()),
+            levels,
+        });
+
+        self.importing.push((canonical.clone(), name.clone()));
+        if let Err(error) = self.push_frame(
+            Frame {
+                function: name,
+                line: keyword.line,
+            },
+            path,
+        ) {
+            self.importing.pop();
+            return Err(error);
+        }
+
+        let mut result = self.in_module(Some(module.clone()), |interpreter| {
+            interpreter.execution_bubble(program.statements)
+        });
+
+        let frame = self.call_stack.pop();
+        self.importing.pop();
 
-            self.environment.define(name, initializer);
+        if let (Err(Interrupt::Error(error)), Some(frame)) = (&mut result, frame) {
+            error.trace.push(frame);
+        }
+        result?;
+
+        self.modules.insert(canonical, module.clone());
+
+        Ok(module)
+    }
+
+    fn execute_print(&mut self, expr: Expr) -> Result<types, Interrupt> {
+        let (line, span) = (expr.line(), expr.span());
+        let value = self.evaluate(expr)?;
+
+        // e.g. when piped into a program which already exited
+        if let Err(reason) = writeln!(io::stdout(), "{}", value) {
+            return Err(RuntimeError::interrupt(
+                Diagnostic::new(line, "", &format!("Couldn't print: {}.", reason))
+                    .with_code("E0006")
+                    .with_span(span),
+            ));
         }
 
         Ok(types::nil)
     }
 
-    fn execute_while(&mut self, stmt: Stmt) -> Result<types, ()> {
-        if let Stmt::While(condition, body) = stmt {
-            let condition = *condition;
-            let body = *body;
+    fn execute_throw(&mut self, keyword: Token, value: Expr) -> Result<types, Interrupt> {
+        let span = keyword.span.to(value.span());
+        let value = self.evaluate(value)?;
+
+        // only reported if nothing catches it
+        let diagnostic =
+            Diagnostic::new(keyword.line, "", &format!("Uncaught exception: {}", value))
+                .with_code("E0008")
+                .with_span(span);
+
+        Err(Interrupt::Error(Box::new(RuntimeError {
+            diagnostic,
+            trace: Vec::new(),
+            thrown: Some(value),
+        })))
+    }
+
+    fn execute_try(
+        &mut self,
+        body: Vec<Stmt>,
+        name: Option<Token>,
+        handler: Vec<Stmt>,
+        finally: Vec<Stmt>,
+    ) -> Result<types, Interrupt> {
+        let mut result = self.execute_block(body);
+
+        if let Some(name) = name {
+            if let Err(Interrupt::Error(error)) = result {
+                let scope = HashMap::from([(name.lexeme, error.into_value())]);
 
-            while is_truthy(&(self.evaluate(condition.clone())?)) {
-                self.execute(body.clone())?;
+                result = self.in_scope(scope, |interpreter| interpreter.execution_bubble(handler));
             }
         }
 
+        // exiting the process skips it, anything else runs it, and
+        // an error raised by it replaces the pending one
+        if !matches!(result, Err(Interrupt::Exit(_))) {
+            self.execute_block(finally)?;
+        }
+
+        result
+    }
+
+    fn execute_var(&mut self, name: Token, initializer: Expr) -> Result<types, Interrupt> {
+        let initializer = self.evaluate(initializer)?;
+
+        self.environment.define(name.lexeme, initializer);
+
+        Ok(types::nil)
+    }
+
+    fn execute_while(&mut self, condition: Expr, body: Stmt) -> Result<types, Interrupt> {
+        while is_truthy(&(self.evaluate(condition.clone())?)) {
+            self.execute(body.clone())?;
+        }
+
         Ok(types::nil)
     }
 
-    fn evaluate(&mut self, expression: Expr) -> Result<types, ()> {
+    pub(crate) fn evaluate(&mut self, expression: Expr) -> Result<types, Interrupt> {
+        self.step(&expression)?;
+
         match expression {
-            Expr::Assign(_, _) => self.evaluate_assign(expression),
-            Expr::Literal(_) => self.evaluate_literal(expression),
-            Expr::Grouping(_) => self.evaluate_parentheses(expression),
-            Expr::Call(_, _, _) => self.evaluate_call(expression),
-            Expr::Logical(_, _, _) => self.evaluate_logical(expression),
-            Expr::Unary(_, _) => self.evaluate_unary(expression),
-            Expr::Binary(_, _, _) => self.evaluate_binary(expression),
-            Expr::Variable(_) => self.get_variable(expression),
+            Expr::Assign(name, value) => self.evaluate_assign(*name, *value),
+            Expr::Literal(value) => evaluate_literal(*value),
+            Expr::Grouping(expr) => self.evaluate(*expr),
+            Expr::Call(callee, paren, arguments) => self.evaluate_call(*callee, *paren, *arguments),
+            Expr::Get(object, name) => self.evaluate_get(*object, *name),
+            Expr::Logical(left, operator, right) => self.evaluate_logical(*left, *operator, *right),
+            Expr::Unary(operator, right) => self.evaluate_unary(*operator, *right),
+            Expr::Binary(left, operator, right) => self.evaluate_binary(*left, *operator, *right),
+            Expr::Variable(name) => self.get_variable(*name),
         }
     }
 
-    fn evaluate_assign(&mut self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Assign(name, value) = expression {
-            let (name, value) = (*name, self.evaluate(*value)?);
+    /// Counts a step, failing if it's one too many or the time is up
+    fn step(&mut self, expression: &Expr) -> Result<(), Interrupt> {
+        self.steps += 1;
 
-            self.environment.assign(name, value)
-        } else {
-            panic!("expression should be an Assign");
+        let (code, message) = match (self.limits.steps, self.limits.time) {
+            (Some(max), _) if self.steps > max => {
+                ("E0014", format!("Step limit of {} exceeded.", max))
+            }
+            (_, Some(max)) if self.ran.elapsed() > max => (
+                "E0015",
+                format!("Time limit of {}s exceeded.", max.as_secs_f64()),
+            ),
+            _ => return Ok(()),
+        };
+
+        Err(RuntimeError::interrupt(
+            Diagnostic::new(expression.line(), "", &message)
+                .with_code(code)
+                .with_span(expression.span()),
+        ))
+    }
+
+    /// Fails at `token` if the strings made so far and `bytes` more
+    /// would be over the limit, e.g. before making a large string
+    pub(crate) fn check_allocation(&self, bytes: usize, token: &Token) -> Result<(), Interrupt> {
+        match self.limits.string_bytes {
+            Some(max) if self.string_bytes.saturating_add(bytes) > max => Err(error(
+                token,
+                "E0017",
+                &format!("String memory limit of {} bytes exceeded.", max),
+            )),
+            _ => Ok(()),
         }
     }
 
-    fn evaluate_literal(&self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Literal(val) = expression {
-            let boxed = val;
+    /// Counts the bytes of strings made, failing past the limit
+    fn allocate(&mut self, bytes: usize, token: &Token) -> Result<(), Interrupt> {
+        self.check_allocation(bytes, token)?;
+        self.string_bytes += bytes;
 
-            match (*boxed).class {
-                TokenVariant::True => Ok(types::boolean(true)),
-                TokenVariant::False => Ok(types::boolean(false)),
-                TokenVariant::Number(val) => Ok(types::number(val)),
-                TokenVariant::String(val) => Ok(types::string(val)),
-                TokenVariant::Nil => Ok(types::nil),
+        Ok(())
+    }
 
-                _ => panic!("Literal holds illegal TokenVariant"),
+    /// Enters a call or an import, failing past the depth limit
+    fn push_frame(&mut self, frame: Frame, token: &Token) -> Result<(), Interrupt> {
+        if let Some(max) = self.limits.depth {
+            if self.call_stack.len() >= max {
+                return Err(error(
+                    token,
+                    "E0016",
+                    &format!("Call depth limit of {} exceeded.", max),
+                ));
             }
-        } else {
-            panic!("expression should be a Literal");
         }
+
+        self.call_stack.push(frame);
+
+        Ok(())
     }
 
-    fn evaluate_parentheses(&mut self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Grouping(val) = expression {
-            Ok(self.evaluate(*val)?)
-        } else {
-            panic!("expression should be a Grouping");
-        }
-    }
-
-    fn evaluate_call(&mut self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Call(callee, paren, arguments) = expression {
-            let callee = self.evaluate(*callee)?;
-
-            match callee {
-                types::function(func) => {
-                    // arity check for user-defined functions only
-                    // this matches the book's implementation
-
-                    // correct number of arguments
-                    if arguments.len() as u8 != func.arity() {
-                        error(
-                            &*paren,
-                            &format!(
-                                "Expected {} arguments but got {}.",
-                                func.arity(),
-                                arguments.len()
-                            ),
-                        );
-                        return Err(());
-                    }
+    fn evaluate_assign(&mut self, name: Token, value: Expr) -> Result<types, Interrupt> {
+        let value = self.evaluate(value)?;
+
+        match self.environment.assign(&name.lexeme, value) {
+            Some(value) => Ok(value),
+            None => Err(error(
+                &name,
+                "E0001",
+                &format!(
+                    "Undefined variable '{}'{}",
+                    &name.lexeme,
+                    self.environment.suggestion(&name.lexeme)
+                ),
+            )),
+        }
+    }
 
-                    func.call(self, *arguments)
-                }
+    fn evaluate_call(
+        &mut self,
+        callee: Expr,
+        paren: Token,
+        arguments: Vec<Expr>,
+    ) -> Result<types, Interrupt> {
+        let callee = self.evaluate(callee)?;
+
+        let mut values = Vec::new();
+        for argument in arguments {
+            values.push(self.evaluate(argument)?);
+        }
 
-                types::native_function(func) => func.call(self, *arguments),
+        let value = self.call_value(callee, values, &paren)?;
 
-                _ => {
-                    error(&*paren, "Can only call functions and classes.");
-                    Err(())
-                }
+        // natives make strings, functions only return nil
+        self.allocate(string_bytes(&value), &paren)?;
+
+        Ok(value)
+    }
+
+    fn call_value(
+        &mut self,
+        callee: types,
+        arguments: Vec<types>,
+        paren: &Token,
+    ) -> Result<types, Interrupt> {
+        let func = match callee {
+            types::function(func) => Box::new(func) as Box<dyn Callable>,
+            types::native_function(func) => func,
+
+            _ => {
+                return Err(error(
+                    paren,
+                    "E0002",
+                    "Can only call functions and classes.",
+                ));
             }
-        } else {
-            panic!("expression should be a function call");
+        };
+
+        // correct number of arguments
+        if arguments.len() != func.arity() as usize {
+            return Err(error(
+                paren,
+                "E0003",
+                &format!(
+                    "Expected {} arguments but got {}.",
+                    func.arity(),
+                    arguments.len()
+                ),
+            ));
         }
+
+        func.call(self, arguments, paren)
     }
 
-    fn evaluate_logical(&mut self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Logical(left, operator, right) = expression {
-            let left = self.evaluate(*left)?;
+    fn evaluate_get(&mut self, object: Expr, name: Token) -> Result<types, Interrupt> {
+        let module = match self.evaluate(object)? {
+            types::module(module) => module,
+            other => {
+                return Err(error(
+                    &name,
+                    "E0012",
+                    &format!("Only modules have members, not a {}.", other.type_name()),
+                ))
+            }
+        };
 
-            let operator = *operator;
+        let member = module.get(&name.lexeme);
 
-            if let TokenVariant::Or = operator.class {
-                if is_truthy(&left) {
-                    return Ok(left);
-                }
-            } else if !is_truthy(&left) {
+        member.ok_or_else(|| {
+            error(
+                &name,
+                "E0012",
+                &format!("Module '{}' has no member '{}'.", module.name, name.lexeme),
+            )
+        })
+    }
+
+    fn evaluate_logical(
+        &mut self,
+        left: Expr,
+        operator: Token,
+        right: Expr,
+    ) -> Result<types, Interrupt> {
+        let left = self.evaluate(left)?;
+
+        if let TokenVariant::Or = operator.class {
+            if is_truthy(&left) {
                 return Ok(left);
             }
-
-            self.evaluate(*right)
-        } else {
-            panic!("expression should be a Logical");
+        } else if !is_truthy(&left) {
+            return Ok(left);
         }
+
+        self.evaluate(right)
     }
 
-    fn evaluate_unary(&mut self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Unary(operator, val) = expression {
-            let operator = *operator;
+    fn evaluate_unary(&mut self, operator: Token, right: Expr) -> Result<types, Interrupt> {
+        match (&operator.class, self.evaluate(right)?) {
+            (TokenVariant::Minus, target) => {
+                let target = check_number_operand(&operator, target)?;
+                Ok(types::number(-target))
+            }
+            (TokenVariant::Bang, target) => Ok(types::boolean(!is_truthy(&target))),
+
+            _ => Err(error(&operator, "E0007", "Invalid unary operator.")),
+        }
+    }
 
-            match (&operator.class, self.evaluate(*val)?) {
-                (TokenVariant::Minus, target) => {
-                    let target = check_number_operand(operator, target)?;
-                    Ok(types::number(-target))
+    fn evaluate_binary(
+        &mut self,
+        left: Expr,
+        operator: Token,
+        right: Expr,
+    ) -> Result<types, Interrupt> {
+        let spans = (left.span(), right.span());
+        let (left, right) = (self.evaluate(left)?, self.evaluate(right)?);
+
+        match operator.class {
+            TokenVariant::Plus => match (left, right) {
+                (types::number(val1), types::number(val2)) => Ok(types::number(val1 + val2)),
+                (types::string(val1), types::string(val2)) => {
+                    self.allocate(val1.len() + val2.len(), &operator)?;
+                    Ok(types::string(val1 + &val2))
                 }
-                (TokenVariant::Bang, target) => Ok(types::boolean(!is_truthy(&target))),
 
-                _ => panic!("Unary should hold Minus and a number, or Bang and any type"),
+                (left, right) => Err(operands_error(
+                    &operator,
+                    "Operands must be two numbers or two strings",
+                    spans,
+                    (&left, &right),
+                )),
+            },
+            TokenVariant::Minus => {
+                let (a, b) = check_number_operands(&operator, spans, left, right)?;
+                Ok(types::number(a - b))
             }
-        } else {
-            panic!("expression should be an Unary");
+            TokenVariant::Slash => {
+                let (a, b) = check_number_operands(&operator, spans, left, right)?;
+                Ok(types::number(a / b))
+            }
+            TokenVariant::Star => {
+                let (a, b) = check_number_operands(&operator, spans, left, right)?;
+                Ok(types::number(a * b))
+            }
+            TokenVariant::Greater => {
+                let order = compare(&operator, spans, left, right)?;
+                Ok(types::boolean(order == Some(Ordering::Greater)))
+            }
+            TokenVariant::GreaterEqual => {
+                let order = compare(&operator, spans, left, right)?;
+                Ok(types::boolean(matches!(
+                    order,
+                    Some(Ordering::Greater | Ordering::Equal)
+                )))
+            }
+            TokenVariant::Less => {
+                let order = compare(&operator, spans, left, right)?;
+                Ok(types::boolean(order == Some(Ordering::Less)))
+            }
+            TokenVariant::LessEqual => {
+                let order = compare(&operator, spans, left, right)?;
+                Ok(types::boolean(matches!(
+                    order,
+                    Some(Ordering::Less | Ordering::Equal)
+                )))
+            }
+            TokenVariant::BangEqual => {
+                self.check_comparison(&operator, spans, (&left, &right))?;
+                Ok(types::boolean(left != right))
+            }
+            TokenVariant::EqualEqual => {
+                self.check_comparison(&operator, spans, (&left, &right))?;
+                Ok(types::boolean(left == right))
+            }
+
+            _ => Err(error(&operator, "E0007", "Invalid binary operator.")),
         }
     }
 
-    fn evaluate_binary(&mut self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Binary(left, operator, right) = expression {
-            let (left, right) = (self.evaluate(*left)?, self.evaluate(*right)?);
-            let operator = *operator;
+    /// Warns about equality between values of different types, which is
+    /// constant, nil aside as it's commonly compared with anything
+    fn check_comparison(
+        &mut self,
+        operator: &Token,
+        spans: (Span, Span),
+        operands: (&types, &types),
+    ) -> Result<(), Interrupt> {
+        let (left, right) = (operands.0.type_name(), operands.1.type_name());
+
+        if left == right || left == "nil" || right == "nil" {
+            return Ok(());
+        }
 
-            match operator.class {
-                TokenVariant::Plus => match (left, right) {
-                    (types::number(val1), types::number(val2)) => Ok(types::number(val1 + val2)),
-                    (types::string(val1), types::string(val2)) => Ok(types::string(val1 + &val2)),
+        let warning = Diagnostic::at(
+            operator,
+            &format!(
+                "Comparison of a {} with a {} is always {}.",
+                left,
+                right,
+                operator.class == TokenVariant::BangEqual
+            ),
+        )
+        .with_code(TYPE_MISMATCH.code)
+        .with_label(spans.0, left)
+        .with_label(spans.1, right);
 
-                    _ => {
-                        error(&operator, "Operands must be two numbers or two strings");
-                        Err(())
-                    }
-                },
-                TokenVariant::Minus => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::number(a - b))
-                }
-                TokenVariant::Slash => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::number(a / b))
-                }
-                TokenVariant::Star => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::number(a * b))
-                }
-                TokenVariant::Greater => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::boolean(a > b))
-                }
-                TokenVariant::GreaterEqual => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::boolean(a >= b))
-                }
-                TokenVariant::Less => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::boolean(a < b))
-                }
-                TokenVariant::LessEqual => {
-                    let (a, b) = check_number_operands(&operator, left, right)?;
-                    Ok(types::boolean(a <= b))
-                }
-                TokenVariant::BangEqual => Ok(types::boolean(left != right)),
-                TokenVariant::EqualEqual => Ok(types::boolean(left == right)),
+        match self.levels().apply(warning) {
+            Some(error) if error.severity == Severity::Error => Err(RuntimeError::interrupt(error)),
+            Some(warning) => {
+                let span = operator.span;
 
-                _ => {
-                    println!("{:?}", operator);
-                    panic!("Illegal TokenVariant for Binary")
+                if self.warned.insert((span.source, span.start, span.end)) {
+                    errors::emit(warning);
                 }
+
+                Ok(())
             }
-        } else {
-            panic!("expression should be a Binary");
+            None => Ok(()),
         }
     }
 
-    fn get_variable(&self, expression: Expr) -> Result<types, ()> {
-        if let Expr::Variable(token) = expression {
-            let original = token.clone();
-
-            match token.class {
-                TokenVariant::Identifier(ident) => {
-                    let attempt = self.environment.get(&ident);
+    fn get_variable(&self, name: Token) -> Result<types, Interrupt> {
+        match self.environment.get(&name.lexeme) {
+            Some(value) => Ok(value),
+            None => Err(error(
+                &name,
+                "E0001",
+                &format!(
+                    "Variable '{}' doesn't exist{}",
+                    &name.lexeme,
+                    self.environment.suggestion(&name.lexeme)
+                ),
+            )),
+        }
+    }
+}
 
-                    if attempt.is_ok() {
-                        attempt
-                    } else {
-                        error(&original, &format!("Variable '{}' doesn't exist.", &ident));
-                        Err(())
-                    }
-                }
+fn evaluate_literal(value: Token) -> Result<types, Interrupt> {
+    match value.class {
+        TokenVariant::True => Ok(types::boolean(true)),
+        TokenVariant::False => Ok(types::boolean(false)),
+        TokenVariant::Number(val) => Ok(types::number(val)),
+        TokenVariant::String(val) => Ok(types::string(val)),
+        TokenVariant::Nil => Ok(types::nil),
 
-                _ => panic!("Variable should hold an Identifier"),
-            }
-        } else {
-            panic!("expression should be a Variable");
-        }
+        _ => Err(error(&value, "E0007", "Invalid literal.")),
     }
 }
 
-fn check_number_operand(operator: Token, operand: types) -> Result<f64, ()> {
+fn check_number_operand(operator: &Token, operand: types) -> Result<f64, Interrupt> {
     if let types::number(val) = operand {
         Ok(val)
     } else {
-        error(&operator, "Operand must be a number");
-        Err(())
+        Err(error(operator, "E0004", "Operand must be a number"))
     }
 }
 
-fn check_number_operands(operator: &Token, left: types, right: types) -> Result<(f64, f64), ()> {
-    if let (types::number(val1), types::number(val2)) = (left, right) {
-        Ok((val1, val2))
+fn check_number_operands(
+    operator: &Token,
+    spans: (Span, Span),
+    left: types,
+    right: types,
+) -> Result<(f64, f64), Interrupt> {
+    if let (types::number(val1), types::number(val2)) = (&left, &right) {
+        Ok((*val1, *val2))
     } else {
-        error(operator, "Operands must be numbers");
-        Err(())
+        Err(operands_error(
+            operator,
+            "Operands must be numbers",
+            spans,
+            (&left, &right),
+        ))
+    }
+}
+
+/// Order of two numbers or two strings, strings comparing by code point,
+/// None when a number is NaN
+fn compare(
+    operator: &Token,
+    spans: (Span, Span),
+    left: types,
+    right: types,
+) -> Result<Option<Ordering>, Interrupt> {
+    match (&left, &right) {
+        (types::number(val1), types::number(val2)) => Ok(val1.partial_cmp(val2)),
+        (types::string(val1), types::string(val2)) => Ok(Some(val1.cmp(val2))),
+
+        _ => Err(operands_error(
+            operator,
+            "Operands must be two numbers or two strings",
+            spans,
+            (&left, &right),
+        )),
+    }
+}
+
+// Points at the operator, each operand labeled with its type
+fn operands_error(
+    operator: &Token,
+    message: &str,
+    spans: (Span, Span),
+    operands: (&types, &types),
+) -> Interrupt {
+    RuntimeError::interrupt(
+        Diagnostic::at(operator, message)
+            .with_code("E0004")
+            .with_label(spans.0, operands.0.type_name())
+            .with_label(spans.1, operands.1.type_name()),
+    )
+}
+
+/// Bytes of the strings in a value, those of lists included
+fn string_bytes(value: &types) -> usize {
+    match value {
+        types::string(string) => string.len(),
+        types::list(values) => values.iter().map(string_bytes).sum(),
+        _ => 0,
     }
 }
 
 /// Ruby: are falsey false and nil
 /// everything else is truthy
-fn is_truthy(object: &types) -> bool {
+pub fn is_truthy(object: &types) -> bool {
     !matches!(object, types::boolean(false) | types::nil)
 }
 
-fn error(token: &Token, message: &str) {
-    errors::report(token.line, &format!(" at '{}'", &token.lexeme), message);
+/// A runtime error located at a token, to be returned
+pub fn error(token: &Token, code: &str, message: &str) -> Interrupt {
+    RuntimeError::interrupt(Diagnostic::at(token, message).with_code(code))
 }
@@ -0,0 +1,166 @@
+/// How warnings are treated: ignored, reported, or reported as errors
+///
+/// Levels are set for a run on the command line, e.g. `--deny=shadowing`,
+/// and for a file by pragma comments at its top, before any code:
+///
+/// ```text
+/// //! allow(type-mismatch) deny(W0008)
+/// ```
+///
+/// `warnings` stands for every lint. Pragmas take precedence over the
+/// command line, and `--deny-warnings` denies the lints left at warn.
+/// Imported files have their own pragmas, those of the importer don't apply.
+use crate::errors::{self, Diagnostic, Severity};
+use crate::lexer::{self, TokenVariant};
+use crate::lint::{self, UNKNOWN_LINT};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "allow" => Some(Level::Allow),
+            "warn" => Some(Level::Warn),
+            "deny" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Levels {
+    // by lint code, lints absent are at warn
+    levels: HashMap<&'static str, Level>,
+    // set for the current file, by pragmas
+    pragmas: HashMap<&'static str, Level>,
+    pub deny_warnings: bool,
+}
+
+impl Levels {
+    /// Sets the level of comma separated lint names or codes,
+    /// returns the first unknown one as an error
+    pub fn set(&mut self, names: &str, level: Level) -> Result<(), String> {
+        set(&mut self.levels, names, level)
+    }
+
+    /// Levels applying to a file, with the warnings about its unknown pragmas
+    pub fn for_file(&self, code: &str) -> (Levels, Vec<Diagnostic>) {
+        let mut levels = self.clone();
+        let mut warnings = Vec::new();
+
+        levels.pragmas.clear();
+
+        let (tokens, _) = lexer::scan_with_trivia(String::from(code), crate::errors::NO_SOURCE);
+
+        let comments = tokens
+            .iter()
+            .filter(|token| token.class != TokenVariant::Newline)
+            .take_while(|token| token.class == TokenVariant::Comment)
+            .filter(|comment| comment.lexeme.starts_with("//!"));
+
+        for comment in comments {
+            for (level, names) in pragmas(&comment.lexeme[3..]) {
+                let result = match Level::from_name(level) {
+                    Some(level) => set(&mut levels.pragmas, names, level),
+                    None => Err(String::from(level)),
+                };
+
+                if let Err(name) = result {
+                    warnings.push(
+                        Diagnostic::new(comment.line, "", &format!("Unknown lint '{}'.", name))
+                            .with_severity(Severity::Warning)
+                            .with_code(UNKNOWN_LINT.code),
+                    );
+                }
+            }
+        }
+
+        (levels, warnings)
+    }
+
+    pub fn level(&self, code: &str) -> Level {
+        let level = self
+            .pragmas
+            .get(code)
+            .or_else(|| self.levels.get(code))
+            .copied()
+            .unwrap_or(Level::Warn);
+
+        if level == Level::Warn && self.deny_warnings {
+            Level::Deny
+        } else {
+            level
+        }
+    }
+
+    /// The warning as reported at its level, None if allowed
+    pub fn apply(&self, warning: Diagnostic) -> Option<Diagnostic> {
+        let level = match &warning.code {
+            Some(code) => self.level(code),
+            None => Level::Warn,
+        };
+
+        match level {
+            Level::Allow => None,
+            Level::Warn => Some(warning.with_severity(Severity::Warning)),
+            Level::Deny => Some(warning.with_severity(Severity::Error)),
+        }
+    }
+
+    /// Reports warnings at their level,
+    /// returns whether any was reported and whether any was denied
+    pub fn report(&self, warnings: Vec<Diagnostic>) -> (bool, bool) {
+        let (mut reported, mut denied) = (false, false);
+
+        for warning in warnings {
+            if let Some(warning) = self.apply(warning) {
+                reported = true;
+                denied |= warning.severity == Severity::Error;
+                errors::emit(warning);
+            }
+        }
+
+        (reported, denied)
+    }
+}
+
+fn set(levels: &mut HashMap<&'static str, Level>, names: &str, level: Level) -> Result<(), String> {
+    for name in names.split(',').map(str::trim) {
+        if name == "warnings" {
+            for lint in lint::LINTS {
+                levels.insert(lint.code, level);
+            }
+            continue;
+        }
+
+        match lint::find(name) {
+            Some(lint) => levels.insert(lint.code, level),
+            None => return Err(String::from(name)),
+        };
+    }
+
+    Ok(())
+}
+
+// `level(names)` groups of a pragma
+fn pragmas(text: &str) -> Vec<(&str, &str)> {
+    let mut result = Vec::new();
+    let mut rest = text;
+
+    while let (Some(open), Some(close)) = (rest.find('('), rest.find(')')) {
+        if close < open {
+            break;
+        }
+
+        result.push((rest[..open].trim(), &rest[open + 1..close]));
+        rest = &rest[close + 1..];
+    }
+
+    result
+}
@@ -1,22 +1,69 @@
-use crate::errors;
+use crate::errors::{self, Diagnostic};
 use std::fmt;
 
-pub fn scan(code: String) -> (Vec<Token>, bool) {
+/// Reserved words, as recognized by `scan`
+pub const KEYWORDS: [&str; 22] = [
+    "and", "catch", "class", "debugger", "else", "false", "finally", "for", "fun", "if", "import",
+    "nil", "or", "print", "return", "super", "this", "throw", "true", "try", "var", "while",
+];
+
+/// `source` is the id the code was registered under with `errors::add_source`,
+/// or `errors::NO_SOURCE`
+pub fn scan(code: String, source: usize) -> (Vec<Token>, bool) {
+    scan_tokens(code, source, false)
+}
+
+/// Like scan, but comments and line breaks are kept as
+/// Comment and Newline tokens, for tools working on the source
+/// as written (e.g. the formatter). The parser doesn't expect them.
+pub fn scan_with_trivia(code: String, source: usize) -> (Vec<Token>, bool) {
+    scan_tokens(code, source, true)
+}
+
+fn scan_tokens(code: String, source: usize, keep_trivia: bool) -> (Vec<Token>, bool) {
+    // byte offset of every character, and of the end
+    let mut offsets: Vec<usize> = code.char_indices().map(|(index, _)| index).collect();
+    offsets.push(code.len());
+
     let mut state = Lexer {
         length: code.chars().count(),
         source: code.chars().collect(),
+        offsets,
+        source_id: source,
 
         start: 0,
         current: 0,
         line: 1,
+        line_start: 0,
+
+        start_line: 1,
+        start_column: 1,
 
         had_error: false,
+        keep_trivia,
     };
 
     let mut tokens: Vec<Token> = Vec::new();
 
+    // A `#!` first line lets scripts be executed directly on Unix,
+    // skip it up to the newline, which is still counted
+    if state.source.starts_with(&['#', '!']) {
+        while state.current + 1 < state.length && peek(&state) != '\n' {
+            state.current += 1;
+        }
+
+        if keep_trivia {
+            let text = state.source[..=state.current].iter().collect();
+            add_token(&mut tokens, TokenVariant::Comment, text, &state);
+        }
+
+        state.current += 1;
+    }
+
     while state.current < state.length {
         state.start = state.current;
+        state.start_line = state.line;
+        state.start_column = state.start - state.line_start + 1;
 
         let c = state.source[state.current];
 
@@ -25,7 +72,13 @@ pub fn scan(code: String) -> (Vec<Token>, bool) {
 
             '\n' => {
                 state.line += 1;
-                None
+                state.line_start = state.current + 1;
+
+                if state.keep_trivia {
+                    Some(TokenVariant::Newline)
+                } else {
+                    None
+                }
             }
 
             '(' => Some(TokenVariant::LeftParen),
@@ -77,7 +130,12 @@ pub fn scan(code: String) -> (Vec<Token>, bool) {
                     while state.current + 1 < state.length && peek(&state) != '\n' {
                         state.current += 1
                     }
-                    None
+
+                    if state.keep_trivia {
+                        Some(TokenVariant::Comment)
+                    } else {
+                        None
+                    }
                 } else {
                     Some(TokenVariant::Slash)
                 }
@@ -85,18 +143,12 @@ pub fn scan(code: String) -> (Vec<Token>, bool) {
 
             '"' => {
                 let res = string(&mut state);
-                match res {
-                    Ok(token) => Some(token),
-                    Err(_) => None,
-                }
+                res.ok()
             }
 
             '0'..='9' => {
                 let res = number(&mut state);
-                match res {
-                    Ok(token) => Some(token),
-                    Err(_) => None,
-                }
+                res.ok()
             }
 
             'A'..='Z' | 'a'..='z' | '_' => {
@@ -104,19 +156,25 @@ pub fn scan(code: String) -> (Vec<Token>, bool) {
                 match &id[..] {
                     // reserved keywords
                     "and" => Some(TokenVariant::And),
+                    "catch" => Some(TokenVariant::Catch),
                     "class" => Some(TokenVariant::Class),
+                    "debugger" => Some(TokenVariant::Debugger),
                     "else" => Some(TokenVariant::Else),
                     "false" => Some(TokenVariant::False),
+                    "finally" => Some(TokenVariant::Finally),
                     "for" => Some(TokenVariant::For),
                     "fun" => Some(TokenVariant::Fun),
                     "if" => Some(TokenVariant::If),
+                    "import" => Some(TokenVariant::Import),
                     "nil" => Some(TokenVariant::Nil),
                     "or" => Some(TokenVariant::Or),
                     "print" => Some(TokenVariant::Print),
                     "return" => Some(TokenVariant::Return),
                     "super" => Some(TokenVariant::Super),
                     "this" => Some(TokenVariant::This),
+                    "throw" => Some(TokenVariant::Throw),
                     "true" => Some(TokenVariant::True),
+                    "try" => Some(TokenVariant::Try),
                     "var" => Some(TokenVariant::Var),
                     "while" => Some(TokenVariant::While),
 
@@ -125,26 +183,37 @@ pub fn scan(code: String) -> (Vec<Token>, bool) {
             }
 
             _ => {
-                errors::error(state.line, &format!("Unexpected character: {}.", c));
-                state.had_error = true;
+                state.error(
+                    state.start,
+                    state.current + 1,
+                    "L0001",
+                    &format!("Unexpected character: {}.", c),
+                );
                 None
             }
         };
 
-        match &matched {
-            Some(_) => add_token(
+        if let Some(variant) = matched {
+            add_token(
                 &mut tokens,
-                matched.unwrap(),
+                variant,
                 state.source[state.start..=state.current].iter().collect(),
                 &state,
-            ),
-            None => (),
+            );
         }
 
         state.current += 1;
     }
 
-    tokens.push(Token::new(TokenVariant::Eof, String::new(), state.line));
+    let end = state.span(state.length, state.length);
+
+    tokens.push(Token::new(
+        TokenVariant::Eof,
+        String::new(),
+        state.line,
+        state.length - state.line_start + 1,
+        end,
+    ));
 
     (tokens, state.had_error)
 }
@@ -160,33 +229,38 @@ fn check(expected: char, state: &mut Lexer) -> bool {
     }
 }
 
-/// Returns the next character that will be read
+/// Returns the next character that will be read, '\0' past the end
 /// Doesn't advance the lexer
-/// Boundary checking should be done upstream
 fn peek(state: &Lexer) -> char {
-    state.source[state.current + 1]
+    state.source.get(state.current + 1).copied().unwrap_or('\0')
 }
 
 /// Like peek but two characters ahead
 fn peek_next(state: &Lexer) -> char {
-    state.source[state.current + 2]
+    state.source.get(state.current + 2).copied().unwrap_or('\0')
 }
 
 fn add_token(tokens: &mut Vec<Token>, variant: TokenVariant, text: String, state: &Lexer) {
-    tokens.push(Token::new(variant, text, state.line));
+    tokens.push(Token::new(
+        variant,
+        text,
+        state.start_line,
+        state.start_column,
+        state.span(state.start, state.current + 1),
+    ));
 }
 
 fn string(state: &mut Lexer) -> Result<TokenVariant, ()> {
     while state.current + 1 < state.length && peek(state) != '"' {
         if peek(state) == '\n' {
             state.line += 1;
+            state.line_start = state.current + 2;
         }
         state.current += 1;
     }
 
     if state.current + 1 >= state.length {
-        errors::error(state.line, "Unterminated string.");
-        state.had_error = true;
+        state.error(state.start, state.length, "L0003", "Unterminated string.");
         return Err(());
     }
 
@@ -201,15 +275,15 @@ fn string(state: &mut Lexer) -> Result<TokenVariant, ()> {
 }
 
 fn number(state: &mut Lexer) -> Result<TokenVariant, ()> {
-    while state.current + 1 < state.length && peek(state).is_digit(10) {
+    while state.current + 1 < state.length && peek(state).is_ascii_digit() {
         state.current += 1;
     }
 
     // Fractional part
-    if peek(state) == '.' && state.current + 2 < state.length && peek_next(state).is_digit(10) {
+    if peek(state) == '.' && state.current + 2 < state.length && peek_next(state).is_ascii_digit() {
         state.current += 1;
 
-        while state.current + 1 < state.length && peek(state).is_digit(10) {
+        while state.current + 1 < state.length && peek(state).is_ascii_digit() {
             state.current += 1;
         }
     }
@@ -222,8 +296,12 @@ fn number(state: &mut Lexer) -> Result<TokenVariant, ()> {
     match literal {
         Ok(num) => Ok(TokenVariant::Number(num)),
         Err(_) => {
-            errors::error(state.line, "Error while parsing Number literal.");
-            state.had_error = true;
+            state.error(
+                state.start,
+                state.current + 1,
+                "L0002",
+                "Error while parsing Number literal.",
+            );
             Err(())
         }
     }
@@ -255,25 +333,59 @@ pub enum TokenVariant {
     Identifier(String), String(String), Number(f64),
 
     // Keywords.
-    And, Class, Else, False, Fun, For, If, Nil, Or,
-    Print, Return, Super, This, True, Var, While,
+    And, Catch, Class, Debugger, Else, False, Finally, Fun, For, If, Import, Nil, Or,
+    Print, Return, Super, This, Throw, True, Try, Var, While,
+
+    // Trivia, only produced by scan_with_trivia.
+    Comment, Newline,
 
     Eof
 }
 
+/// Part of some source code, as byte offsets, `end` excluded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    // id given by errors::add_source
+    pub source: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Smallest span covering both
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            source: self.source,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Token {
     pub class: TokenVariant,
     pub lexeme: String,
+    // where the token starts, both counted from 1
     pub line: usize,
+    pub column: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(class: TokenVariant, lexeme: String, line: usize) -> Token {
+    pub fn new(
+        class: TokenVariant,
+        lexeme: String,
+        line: usize,
+        column: usize,
+        span: Span,
+    ) -> Token {
         Token {
             class,
             lexeme,
             line,
+            column,
+            span,
         }
     }
 }
@@ -287,10 +399,42 @@ impl fmt::Display for Token {
 struct Lexer {
     length: usize,
     source: Vec<char>,
+    // byte offset of each character in the code
+    offsets: Vec<usize>,
+    source_id: usize,
 
     start: usize,
     current: usize,
     line: usize,
+    // index of the first character of the current line
+    line_start: usize,
+
+    // position of the token being scanned
+    start_line: usize,
+    start_column: usize,
 
     had_error: bool,
+    keep_trivia: bool,
+}
+
+impl Lexer {
+    /// Span of the characters from `start` to `end` excluded
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span {
+            source: self.source_id,
+            start: self.offsets[start],
+            end: self.offsets[end],
+        }
+    }
+
+    /// Reports an error over the characters from `start` to `end` excluded
+    fn error(&mut self, start: usize, end: usize, code: &str, message: &str) {
+        // reported on the line the faulty token starts at, as underlined
+        errors::emit(
+            Diagnostic::new(self.start_line, "", message)
+                .with_code(code)
+                .with_span(self.span(start, end)),
+        );
+        self.had_error = true;
+    }
 }
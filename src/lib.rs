@@ -0,0 +1,69 @@
+//! Tree-walk interpreter of the Lox language
+//!
+//! Besides the `yayarlox` command, the interpreter can be embedded:
+//! scripts are parsed with `lex_and_parse` and run by an `Interpreter`,
+//! on which the host defines its own natives and globals, and through
+//! which it calls the functions of scripts. `Interpreter::builder` allows
+//! untrusted scripts only some capabilities, and `Interpreter::limits`
+//! bounds what they may use.
+pub mod capabilities;
+pub mod errors;
+pub mod limits;
+
+pub(crate) mod ast;
+pub(crate) mod cli;
+pub(crate) mod debugger;
+pub(crate) mod explain;
+pub(crate) mod formatter;
+pub(crate) mod interpreter;
+pub(crate) mod json;
+pub(crate) mod levels;
+pub(crate) mod lexer;
+pub(crate) mod lint;
+pub(crate) mod lsp;
+pub(crate) mod natives;
+pub(crate) mod parser;
+pub(crate) mod printer;
+pub(crate) mod resolver;
+pub(crate) mod testing;
+
+pub use interpreter::{types, Interpreter, Interrupt, RuntimeError};
+
+/// Code parsed by `lex_and_parse`, which an `Interpreter` runs
+#[derive(Debug)]
+pub struct Program {
+    pub(crate) statements: Vec<ast::Stmt>,
+    // id of the registered code
+    pub(crate) source: usize,
+}
+
+/// The `yayarlox` command, for its binary
+#[doc(hidden)]
+pub fn command() {
+    cli::main();
+}
+
+/// Lexes and parses code, reporting the errors found,
+/// `name` being the file name shown in diagnostics.
+/// Fails with a summary of the pass which reported errors.
+///
+/// The code is registered for diagnostics to quote it, until
+/// `errors::clear_sources`, or right away if it has errors.
+pub fn lex_and_parse<'a>(name: &str, code: String) -> Result<Program, &'a str> {
+    let source = errors::add_source(name, &code);
+    let (tokens, had_error) = lexer::scan(code, source);
+
+    if had_error {
+        errors::remove_source(source);
+        return Err("Aborting due to error while lexing.");
+    }
+
+    let (statements, had_error) = parser::parse(tokens);
+
+    if had_error {
+        errors::remove_source(source);
+        return Err("Aborting due to error while parsing.");
+    }
+
+    Ok(Program { statements, source })
+}
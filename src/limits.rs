@@ -0,0 +1,39 @@
+/// Limits on what a script may use, for running untrusted code
+///
+/// Exceeding one is a runtime error of its own code, which scripts can
+/// catch, though the limit stays exceeded. Steps, time and string bytes
+/// are counted from the start of each run, by `Interpreter::interpret`
+/// or `Interpreter::call`. Only the call depth is limited by default,
+/// so that runaway recursion is an error rather than a stack overflow.
+use std::time::Duration;
+
+/// Default call depth, fitting in the 8 MiB stack of a main thread, each
+/// call needing a few kilobytes of it, and tens of them in debug builds.
+/// Deeper recursion needs both a higher limit and a thread with a larger
+/// stack, as the `yayarlox` command has, whose default is 10 000 calls.
+pub const EMBEDDED_DEPTH: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct Limits {
+    /// Statements executed plus expressions evaluated
+    pub steps: Option<u64>,
+    /// Wall-clock time of a run
+    pub time: Option<Duration>,
+    /// Calls and imports running at once, `EMBEDDED_DEPTH` by default
+    /// and 10 000 running the `yayarlox` command
+    pub depth: Option<usize>,
+    /// Bytes of the strings made by concatenations and natives,
+    /// counted whether they're still used or not
+    pub string_bytes: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            steps: None,
+            time: None,
+            depth: Some(EMBEDDED_DEPTH),
+            string_bytes: None,
+        }
+    }
+}
@@ -0,0 +1,433 @@
+/// Warnings about code that runs but is likely a mistake
+///
+/// Each lint has a stable code and a name, either of which can be given
+/// to an `allow` comment, e.g. `// allow(unused-variable)` or
+/// `// allow(W0001, W0003)`, silencing it on the comment's line and the next one.
+use crate::ast::*;
+use crate::errors::{self, Diagnostic, Severity};
+use crate::lexer::{self, Span, Token, TokenVariant};
+use crate::natives::natives;
+use crate::resolver::{self, DeclarationKind, Resolution};
+use std::collections::HashMap;
+
+pub struct Lint {
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+pub const UNUSED_VARIABLE: Lint = Lint {
+    code: "W0001",
+    name: "unused-variable",
+};
+pub const UNUSED_PARAMETER: Lint = Lint {
+    code: "W0002",
+    name: "unused-parameter",
+};
+pub const SHADOWING: Lint = Lint {
+    code: "W0003",
+    name: "shadowing",
+};
+pub const UNREACHABLE_CODE: Lint = Lint {
+    code: "W0004",
+    name: "unreachable-code",
+};
+pub const NO_EFFECT: Lint = Lint {
+    code: "W0005",
+    name: "no-effect",
+};
+pub const ASSIGNMENT_IN_CONDITION: Lint = Lint {
+    code: "W0006",
+    name: "assignment-in-condition",
+};
+pub const WRONG_ARITY: Lint = Lint {
+    code: "W0007",
+    name: "wrong-arity",
+};
+pub const REDECLARED_GLOBAL: Lint = Lint {
+    code: "W0008",
+    name: "redeclared-global",
+};
+// reported by the interpreter, as types are only known at runtime
+pub const TYPE_MISMATCH: Lint = Lint {
+    code: "W0009",
+    name: "type-mismatch",
+};
+pub const DIVISION_BY_ZERO: Lint = Lint {
+    code: "W0010",
+    name: "division-by-zero",
+};
+pub const UNKNOWN_LINT: Lint = Lint {
+    code: "W0011",
+    name: "unknown-lint",
+};
+
+pub const LINTS: &[Lint] = &[
+    UNUSED_VARIABLE,
+    UNUSED_PARAMETER,
+    SHADOWING,
+    UNREACHABLE_CODE,
+    NO_EFFECT,
+    ASSIGNMENT_IN_CONDITION,
+    WRONG_ARITY,
+    REDECLARED_GLOBAL,
+    TYPE_MISMATCH,
+    DIVISION_BY_ZERO,
+    UNKNOWN_LINT,
+];
+
+/// Lints also checked when running a script, the others are left to `lint`
+pub const ON_RUN: &[Lint] = &[REDECLARED_GLOBAL, DIVISION_BY_ZERO];
+
+/// Warnings of the lints checked when running a script, see `lint`
+pub fn on_run(code: &str, statements: &[Stmt]) -> Vec<Diagnostic> {
+    lint(code, statements)
+        .into_iter()
+        .filter(|warning| {
+            ON_RUN
+                .iter()
+                .any(|lint| warning.code.as_deref() == Some(lint.code))
+        })
+        .collect()
+}
+
+/// Lint of a code or name
+pub fn find(name: &str) -> Option<&'static Lint> {
+    LINTS
+        .iter()
+        .find(|lint| lint.code.eq_ignore_ascii_case(name) || lint.name == name)
+}
+
+/// Warnings for a script which lexes and parses without errors,
+/// sorted by position. `code` is the script's source, for `allow` comments
+pub fn lint(code: &str, statements: &[Stmt]) -> Vec<Diagnostic> {
+    // resolution errors are reported by whoever checked for them
+    let ((resolution, _), _) = errors::capture(|| resolver::resolve(statements));
+
+    let mut state = Linter {
+        declarations: resolution
+            .references
+            .iter()
+            .filter_map(|reference| Some((reference.name.span.start, reference.declaration?)))
+            .collect(),
+        resolution: &resolution,
+        warnings: Vec::new(),
+    };
+
+    state.unused();
+    state.shadowing();
+    state.redeclared();
+    state.stmts(statements);
+
+    let allowed = allowed(code);
+
+    let mut warnings: Vec<Diagnostic> = state
+        .warnings
+        .into_iter()
+        .filter(|(lint, diagnostic)| {
+            !allowed.iter().any(|(line, names)| {
+                (*line == diagnostic.line || *line + 1 == diagnostic.line)
+                    && names
+                        .iter()
+                        .any(|name| name == lint.code || name == lint.name)
+            })
+        })
+        .map(|(_, diagnostic)| diagnostic)
+        .collect();
+
+    warnings.sort_by_key(|diagnostic| diagnostic.span.map(|span| span.start));
+    warnings
+}
+
+// Lines of `allow` comments, with the lints they name
+fn allowed(code: &str) -> Vec<(usize, Vec<String>)> {
+    let (tokens, _) = lexer::scan_with_trivia(String::from(code), crate::errors::NO_SOURCE);
+
+    tokens
+        .iter()
+        .filter(|token| token.class == TokenVariant::Comment)
+        .filter_map(|comment| {
+            let start = comment.lexeme.find("allow(")? + "allow(".len();
+            let end = start + comment.lexeme[start..].find(')')?;
+
+            let names = comment.lexeme[start..end]
+                .split(',')
+                .map(|name| String::from(name.trim()))
+                .collect();
+
+            Some((comment.line, names))
+        })
+        .collect()
+}
+
+struct Linter<'a> {
+    resolution: &'a Resolution,
+    // declaration each resolved name refers to, by the name's offset
+    declarations: HashMap<usize, usize>,
+
+    warnings: Vec<(Lint, Diagnostic)>,
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, lint: Lint, diagnostic: Diagnostic) {
+        let diagnostic = diagnostic
+            .with_severity(Severity::Warning)
+            .with_code(lint.code);
+
+        self.warnings.push((lint, diagnostic));
+    }
+
+    fn unused(&mut self) {
+        let mut used = vec![false; self.resolution.declarations.len()];
+        for index in self.declarations.values() {
+            used[*index] = true;
+        }
+
+        for (declaration, used) in self.resolution.declarations.iter().zip(used) {
+            // a leading underscore marks names left unused on purpose
+            if used || !declaration.local || declaration.name.lexeme.starts_with('_') {
+                continue;
+            }
+
+            let name = &declaration.name;
+
+            match declaration.kind {
+                DeclarationKind::Variable => self.warn(
+                    UNUSED_VARIABLE,
+                    Diagnostic::at(name, &format!("Unused variable '{}'.", name.lexeme)),
+                ),
+                DeclarationKind::Parameter => self.warn(
+                    UNUSED_PARAMETER,
+                    Diagnostic::at(name, &format!("Unused parameter '{}'.", name.lexeme)),
+                ),
+                DeclarationKind::Function => (),
+            }
+        }
+    }
+
+    fn shadowing(&mut self) {
+        for declaration in self.resolution.declarations.iter() {
+            if let Some(shadowed) = declaration.shadows {
+                let name = &declaration.name;
+                let shadowed = self.resolution.declarations[shadowed].name.span;
+
+                self.warn(
+                    SHADOWING,
+                    Diagnostic::at(
+                        name,
+                        &format!("'{}' shadows a variable of an outer scope.", name.lexeme),
+                    )
+                    .with_label(shadowed, "shadowed declaration here"),
+                );
+            }
+        }
+    }
+
+    fn redeclared(&mut self) {
+        let mut globals: HashMap<&str, Span> = HashMap::new();
+
+        for declaration in self.resolution.declarations.iter() {
+            if declaration.local {
+                continue;
+            }
+
+            let name = &declaration.name;
+            let previous = globals.insert(&name.lexeme, name.span);
+
+            if let (Some(previous), DeclarationKind::Variable) = (previous, declaration.kind) {
+                self.warn(
+                    REDECLARED_GLOBAL,
+                    Diagnostic::at(
+                        name,
+                        &format!("Global '{}' is declared again.", name.lexeme),
+                    )
+                    .with_label(previous, "previous declaration here"),
+                );
+            }
+        }
+    }
+
+    fn stmts(&mut self, statements: &[Stmt]) {
+        let (mut exited, mut reported) = (false, false);
+
+        for stmt in statements {
+            // only the first unreachable statement is reported
+            if exited && !reported {
+                if let Some(span) = stmt.span() {
+                    self.warn(
+                        UNREACHABLE_CODE,
+                        span_diagnostic(span, stmt.line().unwrap_or(0), "Unreachable code."),
+                    );
+                    reported = true;
+                }
+            }
+
+            self.stmt(stmt);
+            exited = exited || self.exits(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block(statements) => self.stmts(statements),
+            Stmt::Debugger(_) | Stmt::Import(_, _, _) => (),
+            Stmt::Expression(expr) => {
+                if !has_effect(expr) {
+                    self.warn(
+                        NO_EFFECT,
+                        span_diagnostic(expr.span(), expr.line(), "Expression has no effect."),
+                    );
+                }
+                self.expr(expr);
+            }
+            Stmt::Function(_, _, body) | Stmt::Test(_, _, body) => self.stmts(body),
+            Stmt::If(condition, then_branch, else_branch) => {
+                if let Some(name) = assignment(condition) {
+                    self.warn(
+                        ASSIGNMENT_IN_CONDITION,
+                        Diagnostic::at(name, "Assignment used as a condition, did you mean '=='?"),
+                    );
+                }
+                self.expr(condition);
+                self.stmt(then_branch);
+                self.stmt(else_branch);
+            }
+            Stmt::Print(expr) | Stmt::Throw(_, expr) | Stmt::Var(_, expr) => self.expr(expr),
+            Stmt::Try(_, body, _, handler, finally) => {
+                self.stmts(body);
+                self.stmts(handler);
+                self.stmts(finally);
+            }
+            Stmt::While(condition, body) => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+        }
+    }
+
+    fn expr(&mut self, expression: &Expr) {
+        match expression {
+            Expr::Assign(_, value) => self.expr(value),
+            Expr::Binary(left, operator, right) => {
+                if operator.class == TokenVariant::Slash && is_zero(right) {
+                    self.warn(
+                        DIVISION_BY_ZERO,
+                        Diagnostic::at(operator, "Division by zero.")
+                            .with_label(right.span(), "this is zero"),
+                    );
+                }
+
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Logical(left, _, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Call(callee, paren, arguments) => {
+                self.arity(callee, paren, arguments.len());
+
+                self.expr(callee);
+                for argument in arguments.iter() {
+                    self.expr(argument);
+                }
+            }
+            Expr::Get(expr, _) | Expr::Grouping(expr) | Expr::Unary(_, expr) => self.expr(expr),
+            Expr::Literal(_) | Expr::Variable(_) => (),
+        }
+    }
+
+    fn arity(&mut self, callee: &Expr, paren: &Token, count: usize) {
+        let name = match callee {
+            Expr::Variable(name) => name,
+            _ => return,
+        };
+
+        let (arity, declared) = match self.declarations.get(&name.span.start) {
+            Some(index) => {
+                let declaration = &self.resolution.declarations[*index];
+                if declaration.kind != DeclarationKind::Function {
+                    return;
+                }
+                (declaration.params.len(), Some(declaration.name.span))
+            }
+            None => match natives()
+                .into_iter()
+                .find(|(native, _)| *native == name.lexeme)
+            {
+                Some((_, native)) => (native.arity() as usize, None),
+                None => return,
+            },
+        };
+
+        if arity == count {
+            return;
+        }
+
+        let mut diagnostic = Diagnostic::at(
+            paren,
+            &format!(
+                "Expected {} arguments but got {} in call to '{}'.",
+                arity, count, name.lexeme
+            ),
+        );
+        if let Some(declared) = declared {
+            diagnostic = diagnostic.with_label(declared, "function declared here");
+        }
+
+        self.warn(WRONG_ARITY, diagnostic);
+    }
+
+    // Whether the statement calls the native `exit`
+    fn exits(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Throw(_, _) => true,
+            Stmt::Expression(expr) => match &**expr {
+                Expr::Call(callee, _, _) => match &**callee {
+                    Expr::Variable(name) => {
+                        name.lexeme == "exit" && !self.declarations.contains_key(&name.span.start)
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+fn span_diagnostic(span: Span, line: usize, message: &str) -> Diagnostic {
+    Diagnostic::new(line, "", message).with_span(span)
+}
+
+// Literal zero, possibly negated or parenthesized
+fn is_zero(expression: &Expr) -> bool {
+    match expression {
+        Expr::Literal(value) => value.class == TokenVariant::Number(0.0),
+        Expr::Grouping(expr) => is_zero(expr),
+        Expr::Unary(operator, expr) => operator.class == TokenVariant::Minus && is_zero(expr),
+        _ => false,
+    }
+}
+
+// False for expressions only computing a value, runtime errors aside
+fn has_effect(expression: &Expr) -> bool {
+    match expression {
+        Expr::Assign(_, _) | Expr::Call(_, _, _) => true,
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            has_effect(left) || has_effect(right)
+        }
+        Expr::Get(expr, _) | Expr::Grouping(expr) | Expr::Unary(_, expr) => has_effect(expr),
+        Expr::Literal(_) | Expr::Variable(_) => false,
+    }
+}
+
+// Name assigned by the condition itself, through groupings,
+// negations and logical operators
+fn assignment(condition: &Expr) -> Option<&Token> {
+    match condition {
+        Expr::Assign(name, _) => Some(name),
+        Expr::Grouping(expr) | Expr::Unary(_, expr) => assignment(expr),
+        Expr::Logical(left, _, right) => assignment(left).or_else(|| assignment(right)),
+        _ => None,
+    }
+}
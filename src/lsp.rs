@@ -0,0 +1,541 @@
+/// Language Server Protocol server, speaking JSON-RPC over stdin and stdout
+///
+/// Documents are fully re-analyzed (lexed, parsed and resolved) on every change.
+/// Positions sent to the client count characters, not UTF-16 code units,
+/// which only matters for scripts that aren't ASCII.
+use crate::errors::{self, Diagnostic, Severity};
+use crate::interpreter::Interpreter;
+use crate::json::{self, Value};
+use crate::lexer::{self, Span, Token};
+use crate::parser;
+use crate::resolver::{self, DeclarationKind, Resolution};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+
+// JSON-RPC error codes
+const PARSE_ERROR: f64 = -32700.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+
+// larger messages are skipped rather than read into memory,
+// scripts being far smaller
+const MAX_MESSAGE_BYTES: usize = 8 * 1024 * 1024;
+
+// LSP enumerations
+const SYNC_FULL: f64 = 1.0;
+const SEVERITY_ERROR: f64 = 1.0;
+const SEVERITY_WARNING: f64 = 2.0;
+const SYMBOL_FUNCTION: f64 = 12.0;
+const SYMBOL_VARIABLE: f64 = 13.0;
+const COMPLETION_FUNCTION: f64 = 3.0;
+const COMPLETION_VARIABLE: f64 = 6.0;
+const COMPLETION_KEYWORD: f64 = 14.0;
+
+/// Serves until the client asks to exit, returns the process' exit status
+pub fn run() -> i32 {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    let mut server = Server {
+        documents: HashMap::new(),
+        shutdown: false,
+        natives: Interpreter::new(Vec::new()).global_names(),
+    };
+
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            // the client went away without asking
+            Ok(None) => return 1,
+            Err(error) => {
+                eprintln!("{}", error);
+                send(&response_error(Value::Null, PARSE_ERROR, &error));
+                continue;
+            }
+        };
+
+        let method = message.get("method").as_str().unwrap_or("");
+
+        if method == "exit" {
+            return if server.shutdown { 0 } else { 1 };
+        }
+
+        server.handle(method, &message);
+    }
+}
+
+struct Document {
+    text: String,
+    resolution: Resolution,
+}
+
+struct Server {
+    // by URI
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+    natives: Vec<String>,
+}
+
+impl Server {
+    fn handle(&mut self, method: &str, message: &Value) {
+        let params = message.get("params");
+        let id = message.get("id").clone();
+
+        let result = match method {
+            "initialize" => Some(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Value::Null)
+            }
+
+            "textDocument/didOpen" => {
+                let document = params.get("textDocument");
+                self.update(document.get("uri"), document.get("text"));
+                None
+            }
+            "textDocument/didChange" => {
+                // full synchronization: the last change holds the whole text
+                let text = params
+                    .get("contentChanges")
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .map_or(&Value::Null, |change| change.get("text"));
+                self.update(params.get("textDocument").get("uri"), text);
+                None
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params.get("textDocument").get("uri").as_str() {
+                    self.documents.remove(uri);
+                    publish_diagnostics(uri, Vec::new());
+                }
+                None
+            }
+
+            "textDocument/definition" => Some(self.definition(params)),
+            "textDocument/references" => Some(self.references(params)),
+            "textDocument/hover" => Some(self.hover(params)),
+            "textDocument/documentSymbol" => Some(self.symbols(params)),
+            "textDocument/completion" => Some(self.completion(params)),
+
+            _ => {
+                // notifications we don't care about are ignored,
+                // unknown requests must be answered
+                if id != Value::Null {
+                    send(&response_error(
+                        id,
+                        METHOD_NOT_FOUND,
+                        &format!("Unknown method {}", method),
+                    ));
+                }
+                return;
+            }
+        };
+
+        if let Some(result) = result {
+            send(&Value::object(vec![
+                ("jsonrpc", Value::string("2.0")),
+                ("id", id),
+                ("result", result),
+            ]));
+        }
+    }
+
+    fn update(&mut self, uri: &Value, text: &Value) {
+        let (uri, text) = match (uri.as_str(), text.as_str()) {
+            (Some(uri), Some(text)) => (uri, text),
+            _ => return,
+        };
+
+        let (document, diagnostics) = analyze(text);
+
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic_json(uri, &document.text, diagnostic))
+            .collect();
+
+        publish_diagnostics(uri, diagnostics);
+
+        self.documents.insert(String::from(uri), document);
+    }
+
+    /// Document and index of the declaration of the name
+    /// at the position given in the request's parameters
+    fn declaration_at<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a Document, usize)> {
+        let uri = params.get("textDocument").get("uri").as_str()?;
+        let document = self.documents.get(uri)?;
+
+        let position = params.get("position");
+        let line = position.get("line").as_f64()? as usize + 1;
+        let column = position.get("character").as_f64()? as usize + 1;
+
+        let contains = |token: &Token| {
+            token.line == line
+                && token.column <= column
+                && column <= token.column + token.lexeme.chars().count()
+        };
+
+        let resolution = &document.resolution;
+
+        let declaration = resolution
+            .declarations
+            .iter()
+            .position(|declaration| contains(&declaration.name))
+            .or_else(|| {
+                resolution
+                    .references
+                    .iter()
+                    .find(|reference| contains(&reference.name))
+                    .and_then(|reference| reference.declaration)
+            })?;
+
+        Some((uri, document, declaration))
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        match self.declaration_at(params) {
+            Some((uri, document, index)) => {
+                location(uri, &document.resolution.declarations[index].name)
+            }
+            None => Value::Null,
+        }
+    }
+
+    fn references(&self, params: &Value) -> Value {
+        let (uri, document, index) = match self.declaration_at(params) {
+            Some(found) => found,
+            None => return Value::Array(Vec::new()),
+        };
+
+        let resolution = &document.resolution;
+        let mut locations = Vec::new();
+
+        let include_declaration = params
+            .get("context")
+            .get("includeDeclaration")
+            .as_bool()
+            .unwrap_or(true);
+
+        if include_declaration {
+            locations.push(location(uri, &resolution.declarations[index].name));
+        }
+
+        for reference in resolution.references.iter() {
+            if reference.declaration == Some(index) {
+                locations.push(location(uri, &reference.name));
+            }
+        }
+
+        Value::Array(locations)
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        match self.declaration_at(params) {
+            Some((_, document, index)) => {
+                let declaration = &document.resolution.declarations[index];
+
+                Value::object(vec![(
+                    "contents",
+                    Value::object(vec![
+                        ("kind", Value::string("markdown")),
+                        (
+                            "value",
+                            Value::String(format!("```lox\n{}\n```", declaration.signature())),
+                        ),
+                    ]),
+                )])
+            }
+            None => Value::Null,
+        }
+    }
+
+    fn symbols(&self, params: &Value) -> Value {
+        let uri = params.get("textDocument").get("uri").as_str().unwrap_or("");
+
+        let document = match self.documents.get(uri) {
+            Some(document) => document,
+            None => return Value::Array(Vec::new()),
+        };
+
+        let mut symbols = Vec::new();
+
+        for declaration in document.resolution.declarations.iter() {
+            let kind = match declaration.kind {
+                DeclarationKind::Function => SYMBOL_FUNCTION,
+                DeclarationKind::Variable => SYMBOL_VARIABLE,
+                DeclarationKind::Parameter => continue,
+            };
+
+            let mut symbol = vec![
+                ("name", Value::string(&declaration.name.lexeme)),
+                ("kind", Value::Number(kind)),
+                ("location", location(uri, &declaration.name)),
+            ];
+
+            if let Some(container) = &declaration.container {
+                symbol.push(("containerName", Value::string(container)));
+            }
+
+            symbols.push(Value::object(symbol));
+        }
+
+        Value::Array(symbols)
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let mut items: Vec<(String, f64)> = lexer::KEYWORDS
+            .iter()
+            .map(|keyword| (String::from(*keyword), COMPLETION_KEYWORD))
+            .collect();
+
+        for native in self.natives.iter() {
+            items.push((native.clone(), COMPLETION_FUNCTION));
+        }
+
+        let uri = params.get("textDocument").get("uri").as_str().unwrap_or("");
+
+        if let Some(document) = self.documents.get(uri) {
+            for declaration in document.resolution.declarations.iter() {
+                let kind = match declaration.kind {
+                    DeclarationKind::Function => COMPLETION_FUNCTION,
+                    _ => COMPLETION_VARIABLE,
+                };
+
+                items.push((declaration.name.lexeme.clone(), kind));
+            }
+        }
+
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        items.dedup_by(|a, b| a.0 == b.0);
+
+        Value::Array(
+            items
+                .into_iter()
+                .map(|(label, kind)| {
+                    Value::object(vec![
+                        ("label", Value::String(label)),
+                        ("kind", Value::Number(kind)),
+                    ])
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Lexes, parses and resolves a document, collecting the errors
+fn analyze(text: &str) -> (Document, Vec<Diagnostic>) {
+    let (resolution, diagnostics) = errors::capture(|| {
+        let (tokens, _) = lexer::scan(String::from(text), errors::NO_SOURCE);
+        let (statements, _) = parser::parse(tokens);
+
+        resolver::resolve(&statements).0
+    });
+
+    let document = Document {
+        text: String::from(text),
+        resolution,
+    };
+
+    (document, diagnostics)
+}
+
+fn capabilities() -> Value {
+    Value::object(vec![(
+        "capabilities",
+        Value::object(vec![
+            ("textDocumentSync", Value::Number(SYNC_FULL)),
+            ("definitionProvider", Value::Bool(true)),
+            ("referencesProvider", Value::Bool(true)),
+            ("hoverProvider", Value::Bool(true)),
+            ("documentSymbolProvider", Value::Bool(true)),
+            ("completionProvider", Value::object(Vec::new())),
+        ]),
+    )])
+}
+
+fn position(line: usize, character: usize) -> Value {
+    Value::object(vec![
+        ("line", Value::Number(line as f64)),
+        ("character", Value::Number(character as f64)),
+    ])
+}
+
+fn location(uri: &str, token: &Token) -> Value {
+    let start = token.column - 1;
+    let end = start + token.lexeme.chars().count();
+
+    Value::object(vec![
+        ("uri", Value::string(uri)),
+        (
+            "range",
+            Value::object(vec![
+                ("start", position(token.line - 1, start)),
+                ("end", position(token.line - 1, end)),
+            ]),
+        ),
+    ])
+}
+
+fn range(text: &str, span: Span) -> Value {
+    let (start_line, start_column) = errors::position(text, span.start);
+    let (end_line, end_column) = errors::position(text, span.end);
+
+    Value::object(vec![
+        ("start", position(start_line - 1, start_column - 1)),
+        ("end", position(end_line - 1, end_column - 1)),
+    ])
+}
+
+fn diagnostic_json(uri: &str, text: &str, diagnostic: &Diagnostic) -> Value {
+    // diagnostics without a span cover their whole line
+    let whole = match diagnostic.span {
+        Some(span) => range(text, span),
+        None => {
+            let line = diagnostic.line.saturating_sub(1);
+            let length = text
+                .lines()
+                .nth(line)
+                .map_or(0, |line| line.chars().count());
+
+            Value::object(vec![
+                ("start", position(line, 0)),
+                ("end", position(line, length)),
+            ])
+        }
+    };
+
+    let related: Vec<Value> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            Value::object(vec![
+                (
+                    "location",
+                    Value::object(vec![
+                        ("uri", Value::string(uri)),
+                        ("range", range(text, label.span)),
+                    ]),
+                ),
+                ("message", Value::string(&label.message)),
+            ])
+        })
+        .collect();
+
+    Value::object(vec![
+        ("range", whole),
+        ("relatedInformation", Value::Array(related)),
+        (
+            "severity",
+            Value::Number(match diagnostic.severity {
+                Severity::Error => SEVERITY_ERROR,
+                Severity::Warning => SEVERITY_WARNING,
+            }),
+        ),
+        (
+            "code",
+            diagnostic
+                .code
+                .as_deref()
+                .map_or(Value::Null, Value::string),
+        ),
+        ("source", Value::string("yayarlox")),
+        (
+            "message",
+            Value::String(format!(
+                "{}{}: {}",
+                match diagnostic.severity {
+                    Severity::Error => "Error",
+                    Severity::Warning => "Warning",
+                },
+                diagnostic.location,
+                diagnostic.message
+            )),
+        ),
+    ])
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) {
+    send(&Value::object(vec![
+        ("jsonrpc", Value::string("2.0")),
+        ("method", Value::string("textDocument/publishDiagnostics")),
+        (
+            "params",
+            Value::object(vec![
+                ("uri", Value::string(uri)),
+                ("diagnostics", Value::Array(diagnostics)),
+            ]),
+        ),
+    ]));
+}
+
+fn response_error(id: Value, code: f64, message: &str) -> Value {
+    Value::object(vec![
+        ("jsonrpc", Value::string("2.0")),
+        ("id", id),
+        (
+            "error",
+            Value::object(vec![
+                ("code", Value::Number(code)),
+                ("message", Value::string(message)),
+            ]),
+        ),
+    ])
+}
+
+// Transport: each message is preceded by a Content-Length header
+// and a blank line
+
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+
+        match input.read_line(&mut header) {
+            Ok(0) => return Ok(None),
+            Ok(_) => (),
+            Err(error) => return Err(error.to_string()),
+        }
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| String::from("Missing Content-Length header"))?;
+
+    if length > MAX_MESSAGE_BYTES {
+        // the body is still consumed, for the next message to be found
+        io::copy(&mut input.take(length as u64), &mut io::sink())
+            .map_err(|error| error.to_string())?;
+
+        return Err(format!(
+            "Message of {} bytes is over the limit of {} bytes",
+            length, MAX_MESSAGE_BYTES
+        ));
+    }
+
+    let mut body = vec![0; length];
+    input
+        .read_exact(&mut body)
+        .map_err(|error| error.to_string())?;
+
+    let body = String::from_utf8(body).map_err(|error| error.to_string())?;
+
+    json::parse(&body).map(Some)
+}
+
+fn send(message: &Value) {
+    let body = message.to_string();
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    // the client is gone if this fails, the next read will tell
+    let _ = write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let _ = output.flush();
+}
@@ -0,0 +1,272 @@
+/// Functions implemented in Rust and available to every script
+use crate::interpreter::{error, is_truthy, types, Callable, ErrorValue, Interpreter, Interrupt};
+use crate::lexer::Token;
+use std::env;
+
+/// Declares a unit struct implementing Callable,
+/// the closure-like body receives the interpreter,
+/// the evaluated arguments and the call's closing parenthesis
+macro_rules! native {
+    (
+        $(#[$doc:meta])*
+        $name:ident, $arity:expr,
+        |$interpreter:tt, $arguments:tt, $paren:tt| $body:block
+    ) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name;
+
+        impl Callable for $name {
+            fn arity(&self) -> u8 {
+                $arity
+            }
+
+            fn call(
+                &self,
+                $interpreter: &mut Interpreter,
+                $arguments: Vec<types>,
+                $paren: &Token,
+            ) -> Result<types, Interrupt> $body
+        }
+    };
+}
+
+mod assert;
+mod io;
+mod math;
+mod strings;
+mod time;
+
+pub use math::{constants, Random};
+
+/// Every native, under the name it's defined with in the global scope
+pub fn natives() -> Vec<(&'static str, Box<dyn Callable>)> {
+    let mut natives: Vec<(&'static str, Box<dyn Callable>)> = vec![
+        ("args", Box::new(NativeArgs)),
+        ("getenv", Box::new(NativeGetenv)),
+        ("exit", Box::new(NativeExit)),
+        ("len", Box::new(NativeLen)),
+        ("get", Box::new(NativeGet)),
+        ("error", Box::new(NativeError)),
+        ("errorMessage", Box::new(NativeErrorMessage)),
+        ("errorLine", Box::new(NativeErrorLine)),
+        ("type", Box::new(NativeType)),
+        ("str", Box::new(NativeStr)),
+        ("num", Box::new(NativeNum)),
+        ("bool", Box::new(NativeBool)),
+    ];
+
+    natives.extend(strings::natives());
+    natives.extend(math::natives());
+    natives.extend(io::natives());
+    natives.extend(time::natives());
+    natives.extend(assert::natives());
+
+    natives
+}
+
+native!(
+    /// Returns the command-line arguments following the script's path
+    NativeArgs, 0,
+    |interpreter, _, _| {
+        Ok(types::list(
+            interpreter
+                .script_args
+                .iter()
+                .map(|arg| types::string(arg.clone()))
+                .collect(),
+        ))
+    }
+);
+
+native!(
+    /// Returns the value of an environment variable, or nil if it isn't set
+    NativeGetenv, 1,
+    |_, arguments, paren| {
+        let name = expect_string(&arguments[0], paren, "Variable name")?;
+
+        match env::var(name) {
+            Ok(value) => Ok(types::string(value)),
+            Err(_) => Ok(types::nil),
+        }
+    }
+);
+
+native!(
+    /// Stops the script, the process exits with the given status
+    NativeExit, 1,
+    |_, arguments, paren| {
+        let code = expect_number(&arguments[0], paren, "Exit status")?;
+
+        if code.fract() != 0.0 || code < i32::MIN as f64 || code > i32::MAX as f64 {
+            return Err(error(paren, "E0005", "Exit status must be an integer."));
+        }
+
+        Err(Interrupt::Exit(code as i32))
+    }
+);
+
+native!(
+    /// Returns the number of elements of a list, or of characters of a string
+    NativeLen, 1,
+    |_, arguments, paren| {
+        match &arguments[0] {
+            types::list(values) => Ok(types::number(values.len() as f64)),
+            types::string(string) => Ok(types::number(string.chars().count() as f64)),
+
+            _ => {
+                Err(error(paren, "E0005", "Argument must be a list or a string."))
+            }
+        }
+    }
+);
+
+native!(
+    /// Returns the element of a list at the given index, starting at 0
+    NativeGet, 2,
+    |_, arguments, paren| {
+        let index = expect_number(&arguments[1], paren, "Index")?;
+
+        match &arguments[0] {
+            types::list(values) => {
+                if index.fract() != 0.0 || index < 0.0 || index as usize >= values.len() {
+                    return Err(error(paren, "E0005", &format!("Index {} out of range.", index)));
+                }
+
+                Ok(values[index as usize].clone())
+            }
+
+            _ => {
+                Err(error(paren, "E0005", "First argument must be a list."))
+            }
+        }
+    }
+);
+
+native!(
+    /// Returns an error value with the given message, at the line of the call
+    NativeError, 1,
+    |_, arguments, paren| {
+        let message = expect_string(&arguments[0], paren, "Message")?;
+
+        Ok(types::error(ErrorValue {
+            message,
+            line: paren.line,
+        }))
+    }
+);
+
+native!(
+    /// Returns the message of an error value
+    NativeErrorMessage, 1,
+    |_, arguments, paren| {
+        let error = expect_error(&arguments[0], paren)?;
+
+        Ok(types::string(error.message))
+    }
+);
+
+native!(
+    /// Returns the line an error value was raised at
+    NativeErrorLine, 1,
+    |_, arguments, paren| {
+        let error = expect_error(&arguments[0], paren)?;
+
+        Ok(types::number(error.line as f64))
+    }
+);
+
+native!(
+    /// Returns the name of the value's type, e.g. "number"
+    NativeType, 1,
+    |_, arguments, _| {
+        Ok(types::string(String::from(arguments[0].type_name())))
+    }
+);
+
+native!(
+    /// Returns the value as `print` shows it
+    NativeStr, 1,
+    |_, arguments, _| {
+        Ok(types::string(arguments[0].to_string()))
+    }
+);
+
+native!(
+    /// Returns the number written in a string, surrounding whitespace
+    /// aside, or nil if there's none. Numbers are returned as they are.
+    NativeNum, 1,
+    |_, arguments, paren| {
+        match &arguments[0] {
+            types::number(val) => Ok(types::number(*val)),
+            types::string(val) => match val.trim().parse::<f64>() {
+                // rejects "inf" and "NaN", which aren't written that way in Lox
+                Ok(number) if number.is_finite() => Ok(types::number(number)),
+                _ => Ok(types::nil),
+            },
+
+            value => Err(error(
+                paren,
+                "E0005",
+                &format!("Can't convert a {} to a number.", value.type_name()),
+            )),
+        }
+    }
+);
+
+native!(
+    /// Returns whether the value is truthy: anything but nil and false
+    NativeBool, 1,
+    |_, arguments, _| {
+        Ok(types::boolean(is_truthy(&arguments[0])))
+    }
+);
+
+// Argument checking
+// `what` describes the argument in the error message
+
+fn expect_string(value: &types, paren: &Token, what: &str) -> Result<String, Interrupt> {
+    if let types::string(val) = value {
+        Ok(val.clone())
+    } else {
+        Err(error(
+            paren,
+            "E0005",
+            &format!("{} must be a string.", what),
+        ))
+    }
+}
+
+fn expect_number(value: &types, paren: &Token, what: &str) -> Result<f64, Interrupt> {
+    if let types::number(val) = value {
+        Ok(*val)
+    } else {
+        Err(error(
+            paren,
+            "E0005",
+            &format!("{} must be a number.", what),
+        ))
+    }
+}
+
+fn expect_index(value: &types, paren: &Token, what: &str) -> Result<usize, Interrupt> {
+    let index = expect_number(value, paren, what)?;
+
+    if index.fract() != 0.0 || index < 0.0 || index > u32::MAX as f64 {
+        return Err(error(
+            paren,
+            "E0005",
+            &format!("{} must be a positive integer.", what),
+        ));
+    }
+
+    Ok(index as usize)
+}
+
+fn expect_error(value: &types, paren: &Token) -> Result<ErrorValue, Interrupt> {
+    if let types::error(error) = value {
+        Ok(error.clone())
+    } else {
+        Err(error(paren, "E0005", "Argument must be an error."))
+    }
+}
@@ -0,0 +1,43 @@
+/// Natives checking what tests expect, failing with E0013
+use super::expect_string;
+use crate::interpreter::{error, is_truthy, types, Callable, Interpreter, Interrupt};
+use crate::lexer::Token;
+
+pub fn natives() -> Vec<(&'static str, Box<dyn Callable>)> {
+    vec![
+        ("assert", Box::new(NativeAssert)),
+        ("assertEqual", Box::new(NativeAssertEqual)),
+    ]
+}
+
+native!(
+    /// Fails with the message unless the condition is truthy
+    NativeAssert, 2,
+    |_, arguments, paren| {
+        let message = expect_string(&arguments[1], paren, "Message")?;
+
+        if is_truthy(&arguments[0]) {
+            Ok(types::nil)
+        } else {
+            Err(error(paren, "E0013", &format!("Assertion failed: {}", message)))
+        }
+    }
+);
+
+native!(
+    /// Fails unless both values are equal, showing them as the REPL would
+    NativeAssertEqual, 2,
+    |_, arguments, paren| {
+        let (actual, expected) = (&arguments[0], &arguments[1]);
+
+        if actual == expected {
+            Ok(types::nil)
+        } else {
+            Err(error(
+                paren,
+                "E0013",
+                &format!("Assertion failed: expected {:?}, got {:?}.", expected, actual),
+            ))
+        }
+    }
+);
@@ -0,0 +1,152 @@
+/// Natives reading the standard input and working on files
+///
+/// Failures are runtime errors naming the path and the reason
+/// given by the system. Paths are checked against the permissions
+/// of the interpreter first.
+use super::expect_string;
+use crate::interpreter::{error, types, Callable, Interpreter, Interrupt};
+use crate::lexer::Token;
+use std::fs;
+use std::io::{self, Write};
+
+pub fn natives() -> Vec<(&'static str, Box<dyn Callable>)> {
+    vec![
+        ("input", Box::new(NativeInput)),
+        ("readLine", Box::new(NativeReadLine)),
+        ("readFile", Box::new(NativeReadFile)),
+        ("writeFile", Box::new(NativeWriteFile)),
+        ("appendFile", Box::new(NativeAppendFile)),
+        ("fileExists", Box::new(NativeFileExists)),
+        ("listDir", Box::new(NativeListDir)),
+    ]
+}
+
+native!(
+    /// Prints a prompt and returns the line typed, or nil at the end of the input
+    NativeInput, 1,
+    |_, arguments, paren| {
+        let prompt = expect_string(&arguments[0], paren, "Prompt")?;
+
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", prompt)
+            .and_then(|_| stdout.flush())
+            .map_err(|reason| io_error(paren, "Couldn't print the prompt", reason))?;
+
+        read_line(paren)
+    }
+);
+
+native!(
+    /// Returns the next line of the standard input, or nil at its end
+    NativeReadLine, 0,
+    |_, _, paren| {
+        read_line(paren)
+    }
+);
+
+native!(
+    /// Returns the content of a file
+    NativeReadFile, 1,
+    |interpreter, arguments, paren| {
+        let path = expect_string(&arguments[0], paren, "Path")?;
+        let checked = interpreter.permissions().check_read(&path, paren)?;
+
+        match fs::read_to_string(&checked) {
+            Ok(content) => Ok(types::string(content)),
+            Err(reason) => Err(io_error(paren, &format!("Couldn't read '{}'", path), reason)),
+        }
+    }
+);
+
+native!(
+    /// Replaces the content of a file, creating it if needed
+    NativeWriteFile, 2,
+    |interpreter, arguments, paren| {
+        let path = expect_string(&arguments[0], paren, "Path")?;
+        let checked = interpreter.permissions().check_write(&path, paren)?;
+        let text = expect_string(&arguments[1], paren, "Text")?;
+
+        match fs::write(&checked, text) {
+            Ok(_) => Ok(types::nil),
+            Err(reason) => Err(io_error(paren, &format!("Couldn't write '{}'", path), reason)),
+        }
+    }
+);
+
+native!(
+    /// Adds text at the end of a file, creating it if needed
+    NativeAppendFile, 2,
+    |interpreter, arguments, paren| {
+        let path = expect_string(&arguments[0], paren, "Path")?;
+        let checked = interpreter.permissions().check_write(&path, paren)?;
+        let text = expect_string(&arguments[1], paren, "Text")?;
+
+        let result = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&checked)
+            .and_then(|mut file| file.write_all(text.as_bytes()));
+
+        match result {
+            Ok(_) => Ok(types::nil),
+            Err(reason) => Err(io_error(paren, &format!("Couldn't append to '{}'", path), reason)),
+        }
+    }
+);
+
+native!(
+    /// Returns whether a file or directory exists at the path
+    NativeFileExists, 1,
+    |interpreter, arguments, paren| {
+        let path = expect_string(&arguments[0], paren, "Path")?;
+        let checked = interpreter.permissions().check_read(&path, paren)?;
+
+        Ok(types::boolean(checked.exists()))
+    }
+);
+
+native!(
+    /// Returns the names of the entries of a directory, sorted
+    NativeListDir, 1,
+    |interpreter, arguments, paren| {
+        let path = expect_string(&arguments[0], paren, "Path")?;
+        let checked = interpreter.permissions().check_read(&path, paren)?;
+        let failed = |reason| io_error(paren, &format!("Couldn't list '{}'", path), reason);
+
+        let mut names = Vec::new();
+
+        for entry in fs::read_dir(&checked).map_err(failed)? {
+            let entry = entry.map_err(failed)?;
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+
+        names.sort();
+
+        Ok(types::list(names.into_iter().map(types::string).collect()))
+    }
+);
+
+// A line without its line break, nil at the end of the input
+fn read_line(paren: &Token) -> Result<types, Interrupt> {
+    let mut line = String::new();
+
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Ok(types::nil),
+        Ok(_) => {
+            if line.ends_with('\n') {
+                line.pop();
+
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+
+            Ok(types::string(line))
+        }
+        Err(reason) => Err(io_error(paren, "Couldn't read the standard input", reason)),
+    }
+}
+
+fn io_error(paren: &Token, what: &str, reason: io::Error) -> Interrupt {
+    error(paren, "E0009", &format!("{}: {}.", what, reason))
+}
@@ -0,0 +1,173 @@
+/// Natives and constants for numbers, and the random generator
+use super::expect_number;
+use crate::interpreter::{error, types, Callable, Interpreter, Interrupt};
+use crate::lexer::Token;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn natives() -> Vec<(&'static str, Box<dyn Callable>)> {
+    vec![
+        ("sqrt", Box::new(NativeSqrt)),
+        ("pow", Box::new(NativePow)),
+        ("abs", Box::new(NativeAbs)),
+        ("floor", Box::new(NativeFloor)),
+        ("ceil", Box::new(NativeCeil)),
+        ("round", Box::new(NativeRound)),
+        ("min", Box::new(NativeMin)),
+        ("max", Box::new(NativeMax)),
+        ("sin", Box::new(NativeSin)),
+        ("cos", Box::new(NativeCos)),
+        ("tan", Box::new(NativeTan)),
+        ("asin", Box::new(NativeAsin)),
+        ("acos", Box::new(NativeAcos)),
+        ("atan", Box::new(NativeAtan)),
+        ("atan2", Box::new(NativeAtan2)),
+        ("exp", Box::new(NativeExp)),
+        ("log", Box::new(NativeLog)),
+        ("log10", Box::new(NativeLog10)),
+        ("log2", Box::new(NativeLog2)),
+        ("random", Box::new(NativeRandom)),
+        ("randomInt", Box::new(NativeRandomInt)),
+        ("seed", Box::new(NativeSeed)),
+    ]
+}
+
+/// Global variables holding numbers, defined next to the natives
+pub fn constants() -> Vec<(&'static str, types)> {
+    vec![
+        ("PI", types::number(std::f64::consts::PI)),
+        ("E", types::number(std::f64::consts::E)),
+        ("INF", types::number(f64::INFINITY)),
+        ("NAN", types::number(f64::NAN)),
+    ]
+}
+
+/// Pseudo-random generator (SplitMix64), seeded from the time unless
+/// `seed(n)` or `--seed` is used. Not suited to cryptography.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Random { state: nanos }
+    }
+}
+
+impl Random {
+    pub fn seed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn float(&mut self) -> f64 {
+        // the 53 high bits, as many as a f64 holds exactly
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Declares a native applying a method of f64 to its numeric arguments
+macro_rules! math {
+    ($name:ident, $method:ident) => {
+        native!($name, 1, |_, arguments, paren| {
+            Ok(types::number(
+                expect_number(&arguments[0], paren, "Argument")?.$method(),
+            ))
+        });
+    };
+    ($name:ident, $method:ident, $what:expr) => {
+        native!($name, 2, |_, arguments, paren| {
+            let value = expect_number(&arguments[0], paren, "First argument")?;
+            let other = expect_number(&arguments[1], paren, $what)?;
+
+            Ok(types::number(value.$method(other)))
+        });
+    };
+}
+
+math!(NativeSqrt, sqrt);
+math!(NativeAbs, abs);
+math!(NativeFloor, floor);
+math!(NativeCeil, ceil);
+// halves are rounded away from zero
+math!(NativeRound, round);
+math!(NativeSin, sin);
+math!(NativeCos, cos);
+math!(NativeTan, tan);
+math!(NativeAsin, asin);
+math!(NativeAcos, acos);
+math!(NativeAtan, atan);
+math!(NativeExp, exp);
+// natural logarithm
+math!(NativeLog, ln);
+math!(NativeLog10, log10);
+math!(NativeLog2, log2);
+
+math!(NativePow, powf, "Exponent");
+math!(NativeMin, min, "Second argument");
+math!(NativeMax, max, "Second argument");
+// angle of the point (x, y), called as atan2(y, x)
+math!(NativeAtan2, atan2, "Second argument");
+
+native!(
+    /// Returns a number between 0 included and 1 excluded
+    NativeRandom, 0,
+    |interpreter, _, _| {
+        Ok(types::number(interpreter.random.float()))
+    }
+);
+
+native!(
+    /// Returns an integer between `low` and `high`, both included
+    NativeRandomInt, 2,
+    |interpreter, arguments, paren| {
+        let low = expect_number(&arguments[0], paren, "Lower bound")?;
+        let high = expect_number(&arguments[1], paren, "Upper bound")?;
+
+        if low.fract() != 0.0 || high.fract() != 0.0 {
+            return Err(error(paren, "E0005", "Bounds must be integers."));
+        }
+
+        if low > high {
+            return Err(error(
+                paren,
+                "E0005",
+                &format!("Lower bound {} is above upper bound {}.", low, high),
+            ));
+        }
+
+        let value = low + (interpreter.random.float() * (high - low + 1.0)).floor();
+
+        Ok(types::number(value.min(high)))
+    }
+);
+
+native!(
+    /// Restarts the random generator from a seed, for reproducible runs
+    NativeSeed, 1,
+    |interpreter, arguments, paren| {
+        let seed = expect_number(&arguments[0], paren, "Seed")?;
+
+        if seed.fract() != 0.0 || !seed.is_finite() {
+            return Err(error(paren, "E0005", "Seed must be an integer."));
+        }
+
+        interpreter.random.seed(seed as i64 as u64);
+
+        Ok(types::nil)
+    }
+);
@@ -0,0 +1,236 @@
+/// Natives working on strings
+///
+/// Positions count characters rather than bytes, from 0, and are
+/// at most u32::MAX.
+use super::{expect_index, expect_string};
+use crate::interpreter::{error, types, Callable, Interpreter, Interrupt};
+use crate::lexer::Token;
+
+pub fn natives() -> Vec<(&'static str, Box<dyn Callable>)> {
+    vec![
+        ("substring", Box::new(NativeSubstring)),
+        ("indexOf", Box::new(NativeIndexOf)),
+        ("contains", Box::new(NativeContains)),
+        ("startsWith", Box::new(NativeStartsWith)),
+        ("endsWith", Box::new(NativeEndsWith)),
+        ("upper", Box::new(NativeUpper)),
+        ("lower", Box::new(NativeLower)),
+        ("trim", Box::new(NativeTrim)),
+        ("split", Box::new(NativeSplit)),
+        ("join", Box::new(NativeJoin)),
+        ("replace", Box::new(NativeReplace)),
+        ("repeat", Box::new(NativeRepeat)),
+        ("charCode", Box::new(NativeCharCode)),
+        ("fromCharCode", Box::new(NativeFromCharCode)),
+    ]
+}
+
+native!(
+    /// Returns the characters from `start` to `end` excluded
+    NativeSubstring, 3,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let start = expect_index(&arguments[1], paren, "Start")?;
+        let end = expect_index(&arguments[2], paren, "End")?;
+
+        let length = string.chars().count();
+
+        if start > end || end > length {
+            return Err(error(
+                paren,
+                "E0005",
+                &format!("Range {}..{} out of bounds for a length of {}.", start, end, length),
+            ));
+        }
+
+        Ok(types::string(string.chars().skip(start).take(end - start).collect()))
+    }
+);
+
+native!(
+    /// Returns the position of the first occurrence of a string, or -1
+    NativeIndexOf, 2,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let searched = expect_string(&arguments[1], paren, "Searched string")?;
+
+        Ok(types::number(match string.find(&searched) {
+            Some(byte) => string[..byte].chars().count() as f64,
+            None => -1.0,
+        }))
+    }
+);
+
+native!(
+    /// Returns whether a string occurs in another
+    NativeContains, 2,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let searched = expect_string(&arguments[1], paren, "Searched string")?;
+
+        Ok(types::boolean(string.contains(&searched)))
+    }
+);
+
+native!(
+    /// Returns whether a string starts with a prefix
+    NativeStartsWith, 2,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let prefix = expect_string(&arguments[1], paren, "Prefix")?;
+
+        Ok(types::boolean(string.starts_with(&prefix)))
+    }
+);
+
+native!(
+    /// Returns whether a string ends with a suffix
+    NativeEndsWith, 2,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let suffix = expect_string(&arguments[1], paren, "Suffix")?;
+
+        Ok(types::boolean(string.ends_with(&suffix)))
+    }
+);
+
+native!(
+    /// Returns the string in upper case
+    NativeUpper, 1,
+    |_, arguments, paren| {
+        Ok(types::string(expect_string(&arguments[0], paren, "Argument")?.to_uppercase()))
+    }
+);
+
+native!(
+    /// Returns the string in lower case
+    NativeLower, 1,
+    |_, arguments, paren| {
+        Ok(types::string(expect_string(&arguments[0], paren, "Argument")?.to_lowercase()))
+    }
+);
+
+native!(
+    /// Removes whitespace at both ends
+    NativeTrim, 1,
+    |_, arguments, paren| {
+        Ok(types::string(expect_string(&arguments[0], paren, "Argument")?.trim().to_string()))
+    }
+);
+
+native!(
+    /// Returns the list of the parts between separators,
+    /// or of the characters for an empty separator
+    NativeSplit, 2,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let separator = expect_string(&arguments[1], paren, "Separator")?;
+
+        let parts = if separator.is_empty() {
+            string.chars().map(|c| types::string(c.to_string())).collect()
+        } else {
+            string
+                .split(&separator)
+                .map(|part| types::string(part.to_string()))
+                .collect()
+        };
+
+        Ok(types::list(parts))
+    }
+);
+
+native!(
+    /// Concatenates the strings of a list, with a separator between them
+    NativeJoin, 2,
+    |_, arguments, paren| {
+        let separator = expect_string(&arguments[1], paren, "Separator")?;
+
+        let values = match &arguments[0] {
+            types::list(values) => values,
+            _ => return Err(error(paren, "E0005", "First argument must be a list.")),
+        };
+
+        let parts = values
+            .iter()
+            .map(|value| expect_string(value, paren, "Every element"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(types::string(parts.join(&separator)))
+    }
+);
+
+native!(
+    /// Replaces every occurrence of a string
+    NativeReplace, 3,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let from = expect_string(&arguments[1], paren, "Replaced string")?;
+        let to = expect_string(&arguments[2], paren, "Replacement")?;
+
+        if from.is_empty() {
+            return Err(error(paren, "E0005", "Replaced string can't be empty."));
+        }
+
+        Ok(types::string(string.replace(&from, &to)))
+    }
+);
+
+// Longest string `repeat` makes, even without a limit on strings,
+// as allocating more would likely abort rather than fail
+const MAX_REPEATED_BYTES: usize = 1 << 30;
+
+native!(
+    /// Returns the string repeated a number of times
+    NativeRepeat, 2,
+    |interpreter, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "String")?;
+        let count = expect_index(&arguments[1], paren, "Count")?;
+        let bytes = string.len().checked_mul(count);
+
+        // rather than failing once it's made
+        interpreter.check_allocation(bytes.unwrap_or(usize::MAX), paren)?;
+
+        match bytes {
+            Some(bytes) if bytes <= MAX_REPEATED_BYTES => Ok(types::string(string.repeat(count))),
+            _ => Err(error(
+                paren,
+                "E0005",
+                &format!(
+                    "Repeated string can't be longer than {} bytes.",
+                    MAX_REPEATED_BYTES
+                ),
+            )),
+        }
+    }
+);
+
+native!(
+    /// Returns the Unicode code point of a one character string
+    NativeCharCode, 1,
+    |_, arguments, paren| {
+        let string = expect_string(&arguments[0], paren, "Argument")?;
+        let mut chars = string.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(types::number(c as u32 as f64)),
+            _ => Err(error(paren, "E0005", "Argument must be a single character.")),
+        }
+    }
+);
+
+native!(
+    /// Returns the one character string of a Unicode code point
+    NativeFromCharCode, 1,
+    |_, arguments, paren| {
+        let code = expect_index(&arguments[0], paren, "Code")?;
+
+        match char::from_u32(code as u32) {
+            Some(c) => Ok(types::string(c.to_string())),
+            None => Err(error(
+                paren,
+                "E0005",
+                &format!("{} isn't a valid character code.", code),
+            )),
+        }
+    }
+);
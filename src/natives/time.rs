@@ -0,0 +1,266 @@
+/// Natives telling the time, waiting, and working with dates
+///
+/// Timestamps are seconds since UNIX EPOCH, possibly fractional or
+/// negative, and dates are in UTC.
+use super::{expect_number, expect_string};
+use crate::interpreter::{error, types, Callable, Interpreter, Interrupt};
+use crate::lexer::Token;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn natives() -> Vec<(&'static str, Box<dyn Callable>)> {
+    vec![
+        ("clock", Box::new(NativeClock)),
+        ("now", Box::new(NativeNow)),
+        ("sleep", Box::new(NativeSleep)),
+        ("utc", Box::new(NativeUtc)),
+        ("formatTime", Box::new(NativeFormatTime)),
+    ]
+}
+
+// about 270,000 years around 1970, as for dates in JavaScript
+const MAX_TIMESTAMP: f64 = 8.64e15;
+
+const DAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+native!(
+    /// Returns the numbers of seconds since UNIX EPOCH, with their fraction
+    NativeClock, 0,
+    |_, _, _| {
+        let seconds = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_secs_f64(),
+            // the system clock is set before 1970
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+
+        Ok(types::number(seconds))
+    }
+);
+
+native!(
+    /// Returns the seconds elapsed since the interpreter started, from a
+    /// clock which never goes back, unlike `clock()`. Meant for measuring.
+    NativeNow, 0,
+    |interpreter, _, _| {
+        Ok(types::number(interpreter.started.elapsed().as_secs_f64()))
+    }
+);
+
+native!(
+    /// Pauses the script for a number of seconds, at most until
+    /// the time limit, which the script then exceeds
+    NativeSleep, 1,
+    |interpreter, arguments, paren| {
+        let seconds = expect_number(&arguments[0], paren, "Duration")?;
+        let left = interpreter
+            .limits
+            .time
+            .map(|max| max.saturating_sub(interpreter.ran.elapsed()));
+
+        match Duration::try_from_secs_f64(seconds) {
+            Ok(duration) => thread::sleep(left.map_or(duration, |left| duration.min(left))),
+            Err(_) => {
+                return Err(error(
+                    paren,
+                    "E0005",
+                    "Duration must be a positive number of seconds.",
+                ))
+            }
+        }
+
+        Ok(types::nil)
+    }
+);
+
+native!(
+    /// Returns the date of a timestamp as a list:
+    /// [year, month, day, hour, minute, second, weekday, day of the year],
+    /// months and days counting from 1, weekdays from 0 for Sunday
+    NativeUtc, 1,
+    |_, arguments, paren| {
+        let date = Date::new(expect_timestamp(&arguments[0], paren)?);
+
+        Ok(types::list(
+            [
+                date.year,
+                date.month,
+                date.day,
+                date.hour,
+                date.minute,
+                date.second,
+                date.weekday,
+                date.yearday,
+            ]
+            .iter()
+            .map(|field| types::number(*field as f64))
+            .collect(),
+        ))
+    }
+);
+
+native!(
+    /// Formats the date of a timestamp following a pattern, where
+    /// directives as in C's strftime are replaced, e.g. "%Y-%m-%d %H:%M"
+    NativeFormatTime, 2,
+    |_, arguments, paren| {
+        let timestamp = expect_timestamp(&arguments[0], paren)?;
+        let pattern = expect_string(&arguments[1], paren, "Pattern")?;
+
+        match format(timestamp, &pattern) {
+            Ok(formatted) => Ok(types::string(formatted)),
+            Err(directive) => Err(error(
+                paren,
+                "E0005",
+                &format!("Unknown directive '%{}' in the pattern.", directive),
+            )),
+        }
+    }
+);
+
+fn expect_timestamp(value: &types, paren: &Token) -> Result<f64, Interrupt> {
+    let timestamp = expect_number(value, paren, "Timestamp")?;
+
+    if timestamp.is_nan() || timestamp.abs() > MAX_TIMESTAMP {
+        return Err(error(paren, "E0005", "Timestamp out of range."));
+    }
+
+    Ok(timestamp)
+}
+
+/// Calendar fields of a timestamp, in UTC
+struct Date {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    // 0 for Sunday
+    weekday: i64,
+    // 1 for January 1st
+    yearday: i64,
+}
+
+impl Date {
+    fn new(timestamp: f64) -> Date {
+        let seconds = timestamp.floor() as i64;
+        let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+        let (year, month, day) = civil_from_days(days);
+
+        Date {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time % 3600 / 60,
+            second: time % 60,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7),
+            yearday: days - days_from_civil(year, 1, 1) + 1,
+        }
+    }
+}
+
+// Howard Hinnant's algorithms, with March as the first month of the year
+// so that leap days come last, and eras of 400 years
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The pattern with its directives replaced,
+/// or the first unknown directive as an error
+fn format(timestamp: f64, pattern: &str) -> Result<String, char> {
+    let date = Date::new(timestamp);
+    let mut result = String::new();
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let directive = chars.next().unwrap_or('%');
+
+        let replacement = match directive {
+            'Y' => format!("{:04}", date.year),
+            'y' => format!("{:02}", date.year.rem_euclid(100)),
+            'm' => format!("{:02}", date.month),
+            'B' => String::from(MONTHS[date.month as usize - 1]),
+            'b' => String::from(&MONTHS[date.month as usize - 1][..3]),
+            'd' => format!("{:02}", date.day),
+            'e' => format!("{:2}", date.day),
+            'j' => format!("{:03}", date.yearday),
+            'A' => String::from(DAYS[date.weekday as usize]),
+            'a' => String::from(&DAYS[date.weekday as usize][..3]),
+            'u' => (if date.weekday == 0 { 7 } else { date.weekday }).to_string(),
+            'H' => format!("{:02}", date.hour),
+            'I' => format!("{:02}", (date.hour + 11) % 12 + 1),
+            'p' => String::from(if date.hour < 12 { "AM" } else { "PM" }),
+            'M' => format!("{:02}", date.minute),
+            'S' => format!("{:02}", date.second),
+            's' => (timestamp.floor() as i64).to_string(),
+            'F' => format!("{:04}-{:02}-{:02}", date.year, date.month, date.day),
+            'T' => format!("{:02}:{:02}:{:02}", date.hour, date.minute, date.second),
+            '%' => String::from("%"),
+
+            _ => return Err(directive),
+        };
+
+        result.push_str(&replacement);
+    }
+
+    Ok(result)
+}
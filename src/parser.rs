@@ -1,5 +1,5 @@
 use crate::ast::*;
-use crate::errors;
+use crate::errors::{self, Diagnostic};
 use crate::lexer::{Token, TokenVariant};
 
 pub fn parse(tokens: Vec<Token>) -> (Vec<Stmt>, bool) {
@@ -8,8 +8,12 @@ pub fn parse(tokens: Vec<Token>) -> (Vec<Stmt>, bool) {
         tokens,
 
         current: 0,
+        depth: 0,
+        links: 0,
 
         had_error: false,
+        gave_up: false,
+        panicking: false,
     };
 
     let mut statements = Vec::new();
@@ -30,9 +34,14 @@ macro_rules! binary {
         $name:ident, $next:ident, [$($variant:ident),*]
     ) => {
         fn $name(&mut self) -> Expr {
+            let links = self.links;
             let mut left = self.$next();
 
             while !self.is_over() && self.fit(vec![$(TokenVariant::$variant),*]) {
+                if self.chained() {
+                    break;
+                }
+
                 let operator = self.get()
                                    .clone();
                 self.advance();
@@ -40,6 +49,7 @@ macro_rules! binary {
                 left = Expr::Binary(Box::new(left), Box::new(operator), Box::new(right));
             }
 
+            self.links = links;
             left
         }
     };
@@ -51,10 +61,54 @@ struct Parser {
     tokens: Vec<Token>,
 
     current: usize,
+    // nested statements and expressions being parsed
+    depth: usize,
+    // links of the left-associative chains being parsed
+    links: usize,
 
     had_error: bool,
+    // set when the rest of the code is skipped, errors aren't reported anymore
+    gave_up: bool,
+    // set after an error until the statement ends, its other errors
+    // mostly following from the first one
+    panicking: bool,
 }
 
+// Code of the error for a missing token
+fn missing(variant: &TokenVariant) -> &'static str {
+    match variant {
+        TokenVariant::Semicolon => "P0002",
+        _ => "P0003",
+    }
+}
+
+// Keywords starting a statement, which misspelled start an expression
+// statement instead: those followed by parentheses make a call, e.g.
+// `whille (x) {...}`, the others a variable, e.g. `vra x = 1;`
+const CALL_KEYWORDS: [&str; 3] = ["for", "if", "while"];
+const NAME_KEYWORDS: [&str; 7] = ["debugger", "fun", "import", "print", "throw", "try", "var"];
+
+// The name an expression statement starts with, if it looks
+// like a misspelled keyword, with that keyword
+fn misspelled_keyword(expr: &Expr) -> Option<(&Token, &'static str)> {
+    let (name, keywords) = match expr {
+        Expr::Variable(name) => (name, &NAME_KEYWORDS[..]),
+        Expr::Call(callee, _, _) => match &**callee {
+            Expr::Variable(name) => (name, &CALL_KEYWORDS[..]),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    errors::closest(&name.lexeme, keywords.iter().copied()).map(|keyword| (&**name, keyword))
+}
+
+// Deeper code is rejected rather than overflowing the stack,
+// here or in any later pass
+const MAX_DEPTH: usize = 200;
+// Links of chains nest the tree too, but cost less stack than real nesting
+const MAX_LINKS: usize = 400;
+
 impl Parser {
     // Progress
 
@@ -63,7 +117,7 @@ impl Parser {
     }
 
     fn back(&mut self) {
-        self.current -= 1;
+        self.current = self.current.saturating_sub(1);
     }
 
     fn advance(&mut self) {
@@ -75,14 +129,14 @@ impl Parser {
         if self.peek().class == variant {
             self.advance();
         } else {
-            self.error(message);
+            self.error(missing(&variant), message);
         }
     }
 
     // checks the current variant
     fn expect(&mut self, variant: TokenVariant, message: &str) {
         if self.get().class != variant {
-            self.error(message);
+            self.error(missing(&variant), message);
         }
     }
 
@@ -91,23 +145,28 @@ impl Parser {
         if self.get().class == variant {
             self.advance();
         } else {
-            self.error(message);
+            self.error(missing(&variant), message);
         }
     }
 
     // Context
-    // Boundary checking should be done beforehand
+    // Past the end, the last token (Eof) is returned, so that
+    // errors don't make the parser read out of bounds
+
+    fn at(&self, index: usize) -> &Token {
+        &self.tokens[index.min(self.length - 1)]
+    }
 
     fn peek(&self) -> &Token {
-        &self.tokens[self.current + 1]
+        self.at(self.current + 1)
     }
 
     fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+        self.at(self.current.saturating_sub(1))
     }
 
     fn get(&self) -> &Token {
-        &self.tokens[self.current]
+        self.at(self.current)
     }
 
     fn fit(&mut self, variants: Vec<TokenVariant>) -> bool {
@@ -127,13 +186,70 @@ impl Parser {
         false
     }
 
+    // Nesting
+
+    // reports code nested too deeply, and then gives up on the rest
+    // as every enclosing construct would report an error too
+    fn too_deep(&mut self) -> bool {
+        if self.depth < MAX_DEPTH {
+            return false;
+        }
+
+        self.give_up("Code is nested too deeply.");
+        true
+    }
+
+    fn give_up(&mut self, message: &str) {
+        self.error("P0007", message);
+        self.current = self.length - 1;
+        self.gave_up = true;
+    }
+
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> T {
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    // left-associative chains, `a + b + c` or `f()()`, are built by loops
+    // but nest what precedes each link one level deeper all the same,
+    // so the caller counts links and restores their count once the chain ends.
+    // Reports and returns true when too long
+    fn chained(&mut self) -> bool {
+        self.links += 1;
+        if self.links < MAX_LINKS {
+            return false;
+        }
+
+        self.give_up("Expression chain is too long.");
+        true
+    }
+
     // Statement grammar
 
     fn declaration(&mut self) -> Stmt {
+        let stmt = self.unsynchronized_declaration();
+
+        if self.panicking {
+            self.synchronize();
+        }
+
+        stmt
+    }
+
+    fn unsynchronized_declaration(&mut self) -> Stmt {
         if self.fit_still(vec![TokenVariant::Var]) {
             self.var_declaration()
         } else if self.fit_still(vec![TokenVariant::Fun]) {
             self.function("function")
+        } else if self.fit_still(vec![TokenVariant::Import]) {
+            self.import_declaration()
+        } else if self.get().class == TokenVariant::Identifier(String::from("test"))
+            && matches!(self.peek().class, TokenVariant::String(_))
+        {
+            self.test_declaration()
         } else {
             self.statement()
         }
@@ -143,7 +259,7 @@ impl Parser {
         match self.peek().class {
             TokenVariant::Identifier(_) => self.advance(),
 
-            _ => self.error(&format!("Expect {} name.", kind)),
+            _ => self.error("P0004", &format!("Expect {} name.", kind)),
         }
 
         let name = self.get().clone();
@@ -161,7 +277,7 @@ impl Parser {
                 parameters.push(self.get().clone());
 
                 if parameters.len() >= 255 {
-                    self.error("Function cannot have more than 255 arguments.")
+                    self.error("P0005", "Function cannot have more than 255 arguments.")
                 }
 
                 if !self.fit(vec![TokenVariant::Comma]) {
@@ -195,7 +311,7 @@ impl Parser {
         match self.peek().class {
             TokenVariant::Identifier(_) => self.advance(),
 
-            _ => self.error("Expect variable name."),
+            _ => self.error("P0004", "Expect variable name."),
         }
 
         let name = self.get().clone();
@@ -208,6 +324,8 @@ impl Parser {
                 TokenVariant::Nil,
                 String::from(""),
                 name.line,
+                name.column,
+                name.span,
             )))
         };
 
@@ -216,7 +334,67 @@ impl Parser {
         Stmt::Var(Box::new(name), Box::new(initializer))
     }
 
+    // `import "path";`, or `import "path" as name;` for a namespace
+    fn import_declaration(&mut self) -> Stmt {
+        let keyword = self.get().clone();
+
+        if !matches!(self.peek().class, TokenVariant::String(_)) {
+            self.error_at(&self.peek().clone(), "P0008", "Expect path after 'import'.");
+        }
+        self.advance();
+
+        let path = self.get().clone();
+
+        // `as` isn't reserved, other identifiers can still be named so
+        let name = if self.peek().class == TokenVariant::Identifier(String::from("as")) {
+            self.advance();
+
+            match self.peek().class {
+                TokenVariant::Identifier(_) => {
+                    self.advance();
+                    Some(self.get().clone())
+                }
+                _ => {
+                    self.error_at(
+                        &self.peek().clone(),
+                        "P0004",
+                        "Expect module name after 'as'.",
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        self.expect_next(TokenVariant::Semicolon, "Expect ';' after import.");
+
+        Stmt::Import(Box::new(keyword), Box::new(path), Box::new(name))
+    }
+
+    // `test "name" { ... }`, `test` being an identifier anywhere else
+    fn test_declaration(&mut self) -> Stmt {
+        let keyword = self.get().clone();
+
+        self.advance();
+        let name = self.get().clone();
+
+        self.advance();
+        self.consume(TokenVariant::LeftBrace, "Expect '{' after test name.");
+        let body = self.block();
+
+        Stmt::Test(Box::new(keyword), Box::new(name), Box::new(body))
+    }
+
     fn statement(&mut self) -> Stmt {
+        if self.too_deep() {
+            return Stmt::Block(Box::default());
+        }
+
+        self.nested(Self::nested_statement)
+    }
+
+    fn nested_statement(&mut self) -> Stmt {
         if self.fit_still(vec![TokenVariant::If]) {
             self.advance();
             self.if_stmt()
@@ -232,23 +410,52 @@ impl Parser {
         } else if self.fit_still(vec![TokenVariant::LeftBrace]) {
             self.advance();
             self.block_stmt()
+        } else if self.fit_still(vec![TokenVariant::Debugger]) {
+            self.debugger_stmt()
+        } else if self.fit_still(vec![TokenVariant::Throw]) {
+            self.throw_stmt()
+        } else if self.fit_still(vec![TokenVariant::Try]) {
+            self.advance();
+            self.try_stmt()
         } else {
             self.expr_stmt()
         }
     }
 
     fn expr_stmt(&mut self) -> Stmt {
-        let expr = self.expression();
+        // the errors of a misspelled statement, like `fro (var i = 0; ...`,
+        // are held back as the misspelling is the one to report
+        let panicking = self.panicking;
+        let (expr, diagnostics) = errors::capture(|| self.expression());
+
+        if !diagnostics.is_empty() || self.peek().class != TokenVariant::Semicolon {
+            if let Some((name, keyword)) = misspelled_keyword(&expr) {
+                let token = self.get().clone();
+
+                self.panicking = panicking;
+                self.report(
+                    Diagnostic::at(&token, "Expect ';' after expression.")
+                        .with_code(missing(&TokenVariant::Semicolon))
+                        .with_label(name.span, &format!("did you mean '{}'?", keyword)),
+                );
+
+                return Stmt::Expression(Box::new(expr));
+            }
+        }
 
+        diagnostics.into_iter().for_each(errors::emit);
         self.expect_next(TokenVariant::Semicolon, "Expect ';' after expression.");
 
         Stmt::Expression(Box::new(expr))
     }
 
+    // the parentheses around conditions belong to the statement,
+    // they aren't a grouping of the condition
     fn if_stmt(&mut self) -> Stmt {
-        self.expect(TokenVariant::LeftParen, "Expect '(' after 'if'.");
+        self.consume(TokenVariant::LeftParen, "Expect '(' after 'if'.");
 
         let condition = self.expression();
+        self.advance();
 
         self.consume(TokenVariant::RightParen, "Expect ')' after if condition.");
 
@@ -258,7 +465,7 @@ impl Parser {
             self.advance();
             self.statement()
         } else {
-            Stmt::Block(Box::new(Vec::new()))
+            Stmt::Block(Box::default())
         };
 
         Stmt::If(
@@ -269,6 +476,11 @@ impl Parser {
     }
 
     fn block_stmt(&mut self) -> Stmt {
+        Stmt::Block(Box::new(self.block()))
+    }
+
+    // statements of a block, from after its opening brace to the closing one
+    fn block(&mut self) -> Vec<Stmt> {
         let mut statements = Vec::new();
 
         while !self.is_over() && !self.fit_still(vec![TokenVariant::RightBrace]) {
@@ -278,13 +490,64 @@ impl Parser {
 
         self.expect(TokenVariant::RightBrace, "Expect '}' after block.");
 
-        Stmt::Block(Box::new(statements))
+        statements
+    }
+
+    fn try_stmt(&mut self) -> Stmt {
+        let keyword = self.previous().clone();
+
+        self.consume(TokenVariant::LeftBrace, "Expect '{' after 'try'.");
+        let body = self.block();
+
+        let (mut name, mut handler) = (None, Vec::new());
+        let caught = self.fit(vec![TokenVariant::Catch]);
+
+        if caught {
+            self.advance();
+            self.consume(TokenVariant::LeftParen, "Expect '(' after 'catch'.");
+
+            match self.get().class {
+                TokenVariant::Identifier(_) => {
+                    name = Some(self.get().clone());
+                    self.advance();
+                }
+                _ => self.error("P0004", "Expect variable name."),
+            }
+
+            self.consume(TokenVariant::RightParen, "Expect ')' after catch variable.");
+            self.consume(TokenVariant::LeftBrace, "Expect '{' before catch body.");
+            handler = self.block();
+        }
+
+        let mut finally = Vec::new();
+
+        if self.fit(vec![TokenVariant::Finally]) {
+            self.advance();
+            self.consume(TokenVariant::LeftBrace, "Expect '{' after 'finally'.");
+            finally = self.block();
+        } else if !caught {
+            let next = self.peek().clone();
+            self.error_at(
+                &next,
+                "P0003",
+                "Expect 'catch' or 'finally' after try block.",
+            );
+        }
+
+        Stmt::Try(
+            Box::new(keyword),
+            Box::new(body),
+            Box::new(name),
+            Box::new(handler),
+            Box::new(finally),
+        )
     }
 
     fn while_stmt(&mut self) -> Stmt {
-        self.expect(TokenVariant::LeftParen, "Expect '(' after 'while'.");
+        self.consume(TokenVariant::LeftParen, "Expect '(' after 'while'.");
 
         let condition = self.expression();
+        self.advance();
 
         self.consume(
             TokenVariant::RightParen,
@@ -304,7 +567,7 @@ impl Parser {
         // following this, don't.
         let initializer = if self.fit_still(vec![TokenVariant::Semicolon]) {
             // Another empty block as a void statement
-            Stmt::Block(Box::new(Vec::new()))
+            Stmt::Block(Box::default())
         } else if self.fit_still(vec![TokenVariant::Var]) {
             self.var_declaration()
         } else {
@@ -320,6 +583,8 @@ impl Parser {
             condition = Expr::Literal(Box::new(Token {
                 lexeme: String::from(";"),
                 line: self.get().line,
+                column: self.get().column,
+                span: self.get().span,
                 class: TokenVariant::True,
             }));
         } else {
@@ -331,21 +596,14 @@ impl Parser {
         // second semicolon
         self.consume(TokenVariant::Semicolon, "Expect ';' after loop condition.");
 
-        let increment: Expr;
-
-        if self.fit_still(vec![TokenVariant::RightParen]) {
-            // the actual value of the increment will be discarded
-            // if none is given, let it be nil
-
-            increment = Expr::Literal(Box::new(Token {
-                lexeme: String::from(";"),
-                line: self.get().line,
-                class: TokenVariant::Nil,
-            }));
+        // none given, the body is the whole loop
+        let increment = if self.fit_still(vec![TokenVariant::RightParen]) {
+            None
         } else {
-            increment = self.expression();
+            let increment = self.expression();
             self.advance();
-        }
+            Some(increment)
+        };
 
         self.consume(TokenVariant::RightParen, "Expect ')' after for clauses.");
 
@@ -353,7 +611,9 @@ impl Parser {
 
         // the body of the while loop: what is actually done,
         // and the increment part
-        body = Stmt::Block(Box::new(vec![body, Stmt::Expression(Box::new(increment))]));
+        if let Some(increment) = increment {
+            body = Stmt::Block(Box::new(vec![body, Stmt::Expression(Box::new(increment))]));
+        }
 
         // wrap it in an actual while-loop with its condition
         body = Stmt::While(Box::new(condition), Box::new(body));
@@ -363,6 +623,25 @@ impl Parser {
         Stmt::Block(Box::new(vec![initializer, body]))
     }
 
+    fn debugger_stmt(&mut self) -> Stmt {
+        let keyword = self.get().clone();
+
+        self.expect_next(TokenVariant::Semicolon, "Expect ';' after 'debugger'.");
+
+        Stmt::Debugger(Box::new(keyword))
+    }
+
+    fn throw_stmt(&mut self) -> Stmt {
+        let keyword = self.get().clone();
+
+        self.advance();
+        let value = self.expression();
+
+        self.expect_next(TokenVariant::Semicolon, "Expect ';' after thrown value.");
+
+        Stmt::Throw(Box::new(keyword), Box::new(value))
+    }
+
     fn print_stmt(&mut self) -> Stmt {
         let value = self.expression();
 
@@ -374,7 +653,11 @@ impl Parser {
     // Expression grammar
 
     fn expression(&mut self) -> Expr {
-        self.assignment()
+        if self.too_deep() {
+            return self.nil();
+        }
+
+        self.nested(Self::assignment)
     }
 
     fn assignment(&mut self) -> Expr {
@@ -382,18 +665,19 @@ impl Parser {
 
         if self.fit(vec![TokenVariant::Equal]) {
             let equal_token = self.get().clone();
+
+            if self.too_deep() {
+                return expr;
+            }
+
             self.advance();
 
-            let value = self.assignment();
+            let value = self.nested(Self::assignment);
 
             match expr {
                 Expr::Variable(name) => return Expr::Assign(name, Box::new(value)),
 
-                _ => errors::report(
-                    equal_token.line,
-                    &equal_token.lexeme,
-                    "Invalid assignment target.",
-                ),
+                _ => self.error_at(&equal_token, "P0006", "Invalid assignment target."),
             }
         }
 
@@ -401,9 +685,14 @@ impl Parser {
     }
 
     fn or(&mut self) -> Expr {
+        let links = self.links;
         let mut expr = self.and();
 
         while !self.is_over() && self.fit(vec![TokenVariant::Or]) {
+            if self.chained() {
+                break;
+            }
+
             let operator = self.get().clone();
 
             self.advance();
@@ -413,22 +702,29 @@ impl Parser {
             expr = Expr::Logical(Box::new(expr), Box::new(operator), Box::new(right))
         }
 
+        self.links = links;
         expr
     }
 
     fn and(&mut self) -> Expr {
+        let links = self.links;
         let mut expr = self.equality();
 
         while !self.is_over() && self.fit(vec![TokenVariant::And]) {
+            if self.chained() {
+                break;
+            }
+
             let operator = self.get().clone();
 
             self.advance();
 
-            let right = self.and();
+            let right = self.equality();
 
             expr = Expr::Logical(Box::new(expr), Box::new(operator), Box::new(right))
         }
 
+        self.links = links;
         expr
     }
 
@@ -445,9 +741,13 @@ impl Parser {
 
     fn unary(&mut self) -> Expr {
         if !self.is_over() && self.fit_still(vec![TokenVariant::Bang, TokenVariant::Minus]) {
+            if self.too_deep() {
+                return self.nil();
+            }
+
             let operator = self.get().clone();
             self.advance();
-            let right = self.unary();
+            let right = self.nested(Self::unary);
             Expr::Unary(Box::new(operator), Box::new(right))
         } else {
             self.call()
@@ -455,18 +755,39 @@ impl Parser {
     }
 
     fn call(&mut self) -> Expr {
+        let links = self.links;
         let mut expr = self.primary();
 
-        // expr(a,b)()
+        // expr(a,b)().name
         loop {
+            if matches!(
+                self.peek().class,
+                TokenVariant::LeftParen | TokenVariant::Dot
+            ) && self.chained()
+            {
+                break;
+            }
+
             if self.fit(vec![TokenVariant::LeftParen]) {
                 self.advance();
                 expr = self.finish_call(expr);
+            } else if self.fit(vec![TokenVariant::Dot]) {
+                match self.peek().class {
+                    TokenVariant::Identifier(_) => self.advance(),
+                    _ => self.error_at(
+                        &self.peek().clone(),
+                        "P0004",
+                        "Expect member name after '.'.",
+                    ),
+                }
+
+                expr = Expr::Get(Box::new(expr), Box::new(self.get().clone()));
             } else {
                 break;
             }
         }
 
+        self.links = links;
         expr
     }
 
@@ -478,7 +799,7 @@ impl Parser {
                 arguments.push(self.expression());
 
                 if arguments.len() >= 255 {
-                    self.error("Function cannot have more than 255 arguments.")
+                    self.error("P0005", "Function cannot have more than 255 arguments.")
                 }
 
                 if !self.fit(vec![TokenVariant::Comma]) {
@@ -522,21 +843,62 @@ impl Parser {
 
             TokenVariant::Identifier(_) => Expr::Variable(Box::new(current.clone())),
 
+            // parsing goes on with nil in place of the expression
             _ => {
-                println!("{:?}", current);
-                panic!("Illegal TokenVariant.");
+                self.error("P0001", "Expect expression.");
+                self.nil()
             }
         }
     }
 
-    fn error(&mut self, message: &str) {
-        let token = &self.tokens[self.current];
+    // nil at the current token, standing for a missing expression
+    fn nil(&self) -> Expr {
+        Expr::Literal(Box::new(Token {
+            class: TokenVariant::Nil,
+            ..self.get().clone()
+        }))
+    }
 
-        if token.class == TokenVariant::Eof {
-            errors::report(token.line, " at end", message);
-        } else {
-            errors::report(token.line, &format!(" at '{}'", token.lexeme), message);
+    fn error(&mut self, code: &str, message: &str) {
+        let token = self.get().clone();
+        self.error_at(&token, code, message);
+    }
+
+    fn error_at(&mut self, token: &Token, code: &str, message: &str) {
+        self.report(Diagnostic::at(token, message).with_code(code));
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        if !self.gave_up && !self.panicking {
+            errors::emit(diagnostic);
         }
         self.had_error = true;
+        self.panicking = true;
+    }
+
+    // skips what's left of a statement with an error, up to its ';'
+    // or the start of the next statement
+    fn synchronize(&mut self) {
+        while !self.is_over()
+            && self.get().class != TokenVariant::Semicolon
+            && !matches!(
+                self.peek().class,
+                TokenVariant::RightBrace
+                    | TokenVariant::LeftBrace
+                    | TokenVariant::Var
+                    | TokenVariant::Fun
+                    | TokenVariant::Import
+                    | TokenVariant::If
+                    | TokenVariant::While
+                    | TokenVariant::For
+                    | TokenVariant::Print
+                    | TokenVariant::Throw
+                    | TokenVariant::Try
+            )
+        {
+            self.advance();
+        }
+
+        self.panicking = false;
     }
 }
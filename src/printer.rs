@@ -0,0 +1,288 @@
+/// Textual representations of the AST
+///
+/// Two forms are available:
+/// - Lisp-like S-expressions, `(* (- 123) (group 45.67))`, making precedence explicit
+/// - Lox source, which parses back to an equivalent tree
+use crate::ast::*;
+use crate::lexer::{Token, TokenVariant};
+
+const INDENT: &str = "    ";
+
+pub fn sexpr(statements: &[Stmt]) -> String {
+    statements
+        .iter()
+        .map(|stmt| sexpr_stmt(stmt) + "\n")
+        .collect()
+}
+
+pub fn lox(statements: &[Stmt]) -> String {
+    statements
+        .iter()
+        .map(|stmt| lox_stmt(stmt, 0) + "\n")
+        .collect()
+}
+
+// S-expressions
+
+pub fn sexpr_expr(expression: &Expr) -> String {
+    match expression {
+        Expr::Assign(name, value) => parenthesize("=", &[&name.lexeme, &sexpr_expr(value)]),
+        Expr::Binary(left, operator, right) | Expr::Logical(left, operator, right) => {
+            parenthesize(&operator.lexeme, &[&sexpr_expr(left), &sexpr_expr(right)])
+        }
+        Expr::Call(callee, _, arguments) => {
+            let mut parts = vec![sexpr_expr(callee)];
+            parts.extend(arguments.iter().map(sexpr_expr));
+
+            parenthesize(
+                "call",
+                &parts.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+        }
+        Expr::Get(object, name) => parenthesize(".", &[&sexpr_expr(object), &name.lexeme]),
+        Expr::Grouping(expr) => parenthesize("group", &[&sexpr_expr(expr)]),
+        Expr::Literal(value) => literal(value),
+        Expr::Unary(operator, right) => parenthesize(&operator.lexeme, &[&sexpr_expr(right)]),
+        Expr::Variable(name) => name.lexeme.clone(),
+    }
+}
+
+pub fn sexpr_stmt(stmt: &Stmt) -> String {
+    match stmt {
+        Stmt::Block(statements) => {
+            let parts: Vec<String> = statements.iter().map(sexpr_stmt).collect();
+
+            parenthesize(
+                "block",
+                &parts.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+        }
+        Stmt::Debugger(_) => String::from("(debugger)"),
+        Stmt::Expression(expr) => parenthesize("expr", &[&sexpr_expr(expr)]),
+        Stmt::Import(_, path, name) => match &**name {
+            Some(name) => parenthesize("import", &[&path.lexeme, "as", &name.lexeme]),
+            None => parenthesize("import", &[&path.lexeme]),
+        },
+        Stmt::Function(name, params, body) => {
+            let params = params
+                .iter()
+                .map(|param| param.lexeme.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let mut parts = vec![name.lexeme.clone(), format!("({})", params)];
+            parts.extend(body.iter().map(sexpr_stmt));
+
+            parenthesize("fun", &parts.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+        Stmt::If(condition, then_branch, else_branch) => parenthesize(
+            "if",
+            &[
+                &sexpr_expr(condition),
+                &sexpr_stmt(then_branch),
+                &sexpr_stmt(else_branch),
+            ],
+        ),
+        Stmt::Print(expr) => parenthesize("print", &[&sexpr_expr(expr)]),
+        Stmt::Throw(_, value) => parenthesize("throw", &[&sexpr_expr(value)]),
+        Stmt::Test(_, name, body) => {
+            let mut parts = vec![name.lexeme.clone()];
+            parts.extend(body.iter().map(sexpr_stmt));
+
+            parenthesize(
+                "test",
+                &parts.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+        }
+        Stmt::Try(_, body, name, handler, finally) => {
+            let block =
+                |statements: &[Stmt]| sexpr_stmt(&Stmt::Block(Box::new(statements.to_vec())));
+
+            let mut parts = vec![block(body)];
+
+            if let Some(name) = &**name {
+                parts.push(parenthesize("catch", &[&name.lexeme, &block(handler)]));
+            }
+            if !finally.is_empty() {
+                parts.push(parenthesize("finally", &[&block(finally)]));
+            }
+
+            parenthesize("try", &parts.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+        Stmt::Var(name, initializer) => {
+            parenthesize("var", &[&name.lexeme, &sexpr_expr(initializer)])
+        }
+        Stmt::While(condition, body) => {
+            parenthesize("while", &[&sexpr_expr(condition), &sexpr_stmt(body)])
+        }
+    }
+}
+
+fn parenthesize(name: &str, parts: &[&str]) -> String {
+    let mut result = format!("({}", name);
+
+    for part in parts {
+        result.push(' ');
+        result.push_str(part);
+    }
+
+    result.push(')');
+    result
+}
+
+// Lox source
+
+pub fn lox_expr(expression: &Expr) -> String {
+    match expression {
+        Expr::Assign(name, value) => format!("{} = {}", name.lexeme, lox_expr(value)),
+        Expr::Binary(left, operator, right) | Expr::Logical(left, operator, right) => {
+            format!("{} {} {}", lox_expr(left), operator.lexeme, lox_expr(right))
+        }
+        Expr::Call(callee, _, arguments) => format!(
+            "{}({})",
+            lox_expr(callee),
+            arguments
+                .iter()
+                .map(lox_expr)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        // groupings are the only parentheses needed: the tree comes
+        // from the parser, so precedence already matches the source
+        Expr::Get(object, name) => format!("{}.{}", lox_expr(object), name.lexeme),
+        Expr::Grouping(expr) => format!("({})", lox_expr(expr)),
+        Expr::Literal(value) => literal(value),
+        Expr::Unary(operator, right) => format!("{}{}", operator.lexeme, lox_expr(right)),
+        Expr::Variable(name) => name.lexeme.clone(),
+    }
+}
+
+pub fn lox_stmt(stmt: &Stmt, depth: usize) -> String {
+    let indent = INDENT.repeat(depth);
+
+    match stmt {
+        Stmt::Block(statements) => format!("{}{}", indent, lox_block(statements, depth)),
+        Stmt::Debugger(_) => format!("{}debugger;", indent),
+        Stmt::Expression(expr) => format!("{}{};", indent, lox_expr(expr)),
+        Stmt::Import(_, path, name) => match &**name {
+            Some(name) => format!("{}import {} as {};", indent, path.lexeme, name.lexeme),
+            None => format!("{}import {};", indent, path.lexeme),
+        },
+        Stmt::Function(name, params, body) => format!(
+            "{}fun {}({}) {}",
+            indent,
+            name.lexeme,
+            params
+                .iter()
+                .map(|param| param.lexeme.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            lox_block(body, depth)
+        ),
+        Stmt::If(condition, then_branch, else_branch) => {
+            let mut result = format!(
+                "{}if {}{}",
+                indent,
+                lox_condition(condition),
+                lox_body(then_branch, depth)
+            );
+
+            // an absent else branch is parsed as an empty block
+            if !matches!(&**else_branch, Stmt::Block(statements) if statements.is_empty()) {
+                if let Stmt::Block(_) = &**then_branch {
+                    result.push_str(" else");
+                } else {
+                    result.push_str(&format!("\n{}else", indent));
+                }
+
+                if let Stmt::If(_, _, _) = &**else_branch {
+                    result.push(' ');
+                    result.push_str(lox_stmt(else_branch, depth).trim_start());
+                } else {
+                    result.push_str(&lox_body(else_branch, depth));
+                }
+            }
+
+            result
+        }
+        Stmt::Print(expr) => format!("{}print {};", indent, lox_expr(expr)),
+        Stmt::Test(_, name, body) => {
+            format!("{}test {} {}", indent, name.lexeme, lox_block(body, depth))
+        }
+        Stmt::Throw(_, value) => format!("{}throw {};", indent, lox_expr(value)),
+        Stmt::Try(_, body, name, handler, finally) => {
+            let mut result = format!("{}try {}", indent, lox_block(body, depth));
+
+            if let Some(name) = &**name {
+                result.push_str(&format!(
+                    " catch ({}) {}",
+                    name.lexeme,
+                    lox_block(handler, depth)
+                ));
+            }
+
+            // an absent finally is parsed as an empty one
+            if !finally.is_empty() || name.is_none() {
+                result.push_str(&format!(" finally {}", lox_block(finally, depth)));
+            }
+
+            result
+        }
+        Stmt::Var(name, initializer) => match &**initializer {
+            Expr::Literal(value) if value.class == TokenVariant::Nil => {
+                format!("{}var {};", indent, name.lexeme)
+            }
+            _ => format!("{}var {} = {};", indent, name.lexeme, lox_expr(initializer)),
+        },
+        Stmt::While(condition, body) => format!(
+            "{}while {}{}",
+            indent,
+            lox_condition(condition),
+            lox_body(body, depth)
+        ),
+    }
+}
+
+/// The parentheses around a condition are part of the statement
+fn lox_condition(condition: &Expr) -> String {
+    format!("({})", lox_expr(condition))
+}
+
+/// Body of a control flow statement: a block stays on the same line,
+/// anything else goes on the next one, indented
+fn lox_body(stmt: &Stmt, depth: usize) -> String {
+    match stmt {
+        Stmt::Block(statements) => format!(" {}", lox_block(statements, depth)),
+        _ => format!("\n{}", lox_stmt(stmt, depth + 1)),
+    }
+}
+
+/// Braces and statements of a block, without the leading indentation
+fn lox_block(statements: &[Stmt], depth: usize) -> String {
+    if statements.is_empty() {
+        return String::from("{}");
+    }
+
+    let mut result = String::from("{\n");
+
+    for stmt in statements {
+        result.push_str(&lox_stmt(stmt, depth + 1));
+        result.push('\n');
+    }
+
+    result.push_str(&INDENT.repeat(depth));
+    result.push('}');
+    result
+}
+
+// Literals are printed from their variant rather than their lexeme,
+// as tokens made up by the parser (e.g. in for loops) have none
+fn literal(value: &Token) -> String {
+    match &value.class {
+        TokenVariant::Number(num) => num.to_string(),
+        TokenVariant::String(string) => format!("\"{}\"", string),
+        TokenVariant::True => String::from("true"),
+        TokenVariant::False => String::from("false"),
+        _ => String::from("nil"),
+    }
+}
@@ -0,0 +1,312 @@
+/// Static resolution of names
+///
+/// Links every use of a name to the declaration it refers to,
+/// following lexical scoping, and reports misuses of local variables.
+/// Globals may be used before being declared (e.g. in a function body),
+/// so uses that don't match anything declared yet refer to the last
+/// global of that name, if any. Natives are left unresolved.
+use crate::ast::*;
+use crate::errors::{self, Diagnostic};
+use crate::lexer::Token;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeclarationKind {
+    Variable,
+    Function,
+    Parameter,
+}
+
+#[derive(Clone, Debug)]
+pub struct Declaration {
+    pub name: Token,
+    pub kind: DeclarationKind,
+    // name of the function it's declared in, None for the top level
+    pub container: Option<String>,
+    // parameters, for functions
+    pub params: Vec<Token>,
+    // declared in a block or a function rather than at the top level
+    pub local: bool,
+    // declaration of the same name it hides, from an outer scope
+    pub shadows: Option<usize>,
+}
+
+impl Declaration {
+    /// How the declaration reads in the source, e.g. `fun factorial(n)`
+    pub fn signature(&self) -> String {
+        match self.kind {
+            DeclarationKind::Variable => format!("var {}", self.name.lexeme),
+            DeclarationKind::Function => format!(
+                "fun {}({})",
+                self.name.lexeme,
+                self.params
+                    .iter()
+                    .map(|param| param.lexeme.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            DeclarationKind::Parameter => format!(
+                "{} (parameter of {})",
+                self.name.lexeme,
+                self.container.as_deref().unwrap_or("?")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub name: Token,
+    // index in Resolution::declarations
+    pub declaration: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub declarations: Vec<Declaration>,
+    pub references: Vec<Reference>,
+}
+
+/// Returns the resolution, and whether an error was reported
+pub fn resolve(statements: &[Stmt]) -> (Resolution, bool) {
+    let mut state = Resolver {
+        resolution: Resolution::default(),
+        globals: HashMap::new(),
+        scopes: Vec::new(),
+        functions: Vec::new(),
+        unresolved: Vec::new(),
+
+        had_error: false,
+    };
+
+    for stmt in statements {
+        state.stmt(stmt);
+    }
+
+    // late binding of globals
+    for index in state.unresolved {
+        let reference = &mut state.resolution.references[index];
+        reference.declaration = state.globals.get(&reference.name.lexeme).copied();
+    }
+
+    (state.resolution, state.had_error)
+}
+
+struct Resolver {
+    resolution: Resolution,
+
+    // last declaration of each global name
+    globals: HashMap<String, usize>,
+    // innermost last, each mapping names to a declaration and
+    // whether its initializer has been resolved
+    scopes: Vec<HashMap<String, (usize, bool)>>,
+    // functions being resolved, innermost last
+    functions: Vec<String>,
+    // references left for the end, as they may be globals declared later
+    unresolved: Vec<usize>,
+
+    had_error: bool,
+}
+
+impl Resolver {
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block(statements) => self.block(statements),
+            Stmt::Debugger(_) => (),
+            Stmt::Expression(expr) | Stmt::Print(expr) | Stmt::Throw(_, expr) => self.expr(expr),
+            Stmt::Function(name, params, body) => {
+                let index = self.declare(name, DeclarationKind::Function, params);
+                self.define(name, index);
+
+                self.functions.push(name.lexeme.clone());
+                self.scopes.push(HashMap::new());
+
+                for param in params.iter() {
+                    let index = self.declare(param, DeclarationKind::Parameter, &[]);
+                    self.define(param, index);
+                }
+
+                for stmt in body.iter() {
+                    self.stmt(stmt);
+                }
+
+                self.scopes.pop();
+                self.functions.pop();
+            }
+            Stmt::If(condition, then_branch, else_branch) => {
+                self.expr(condition);
+                self.stmt(then_branch);
+                self.stmt(else_branch);
+            }
+            // names imported without a namespace are only known at runtime,
+            // so references to them are left unresolved, like natives
+            Stmt::Import(_, _, name) => {
+                if let Some(name) = &**name {
+                    let index = self.declare(name, DeclarationKind::Variable, &[]);
+                    self.define(name, index);
+                }
+            }
+            Stmt::Test(keyword, _, body) => {
+                // only those of the top level are found and run
+                if !self.scopes.is_empty() {
+                    self.error(
+                        Diagnostic::at(keyword, "Tests must be at the top level.")
+                            .with_code("R0003"),
+                    );
+                }
+
+                self.block(body);
+            }
+            Stmt::Try(_, body, name, handler, finally) => {
+                self.block(body);
+
+                // the caught value is a variable of the handler's scope
+                self.scopes.push(HashMap::new());
+                if let Some(name) = &**name {
+                    let index = self.declare(name, DeclarationKind::Variable, &[]);
+                    self.define(name, index);
+                }
+                for stmt in handler.iter() {
+                    self.stmt(stmt);
+                }
+                self.scopes.pop();
+
+                self.block(finally);
+            }
+            Stmt::Var(name, initializer) => {
+                let index = self.declare(name, DeclarationKind::Variable, &[]);
+                self.expr(initializer);
+                self.define(name, index);
+            }
+            Stmt::While(condition, body) => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+        }
+    }
+
+    fn block(&mut self, statements: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        for stmt in statements.iter() {
+            self.stmt(stmt);
+        }
+        self.scopes.pop();
+    }
+
+    fn expr(&mut self, expression: &Expr) {
+        match expression {
+            Expr::Assign(name, value) => {
+                self.expr(value);
+                self.reference(name);
+            }
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Call(callee, _, arguments) => {
+                self.expr(callee);
+                for argument in arguments.iter() {
+                    self.expr(argument);
+                }
+            }
+            Expr::Get(expr, _) | Expr::Grouping(expr) | Expr::Unary(_, expr) => self.expr(expr),
+            Expr::Literal(_) => (),
+            Expr::Variable(name) => {
+                if let Some(scope) = self.scopes.last() {
+                    if let Some((index, false)) = scope.get(&name.lexeme) {
+                        let declared = self.resolution.declarations[*index].name.span;
+
+                        self.error(
+                            Diagnostic::at(
+                                name,
+                                "Can't read local variable in its own initializer.",
+                            )
+                            .with_code("R0002")
+                            .with_label(declared, "variable declared here"),
+                        );
+                    }
+                }
+
+                self.reference(name);
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Token, kind: DeclarationKind, params: &[Token]) -> usize {
+        let index = self.resolution.declarations.len();
+
+        let shadows = match self.scopes.split_last() {
+            Some((_, outer)) => outer
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(&name.lexeme))
+                .map(|(index, _)| *index)
+                .or_else(|| self.globals.get(&name.lexeme).copied()),
+            None => None,
+        };
+
+        self.resolution.declarations.push(Declaration {
+            name: name.clone(),
+            kind,
+            container: self.functions.last().cloned(),
+            params: params.to_vec(),
+            local: !self.scopes.is_empty(),
+            shadows,
+        });
+
+        match self.scopes.last_mut() {
+            Some(scope) => {
+                if let Some((previous, _)) = scope.get(&name.lexeme) {
+                    let previous = self.resolution.declarations[*previous].name.span;
+
+                    self.error(
+                        Diagnostic::at(name, "Already a variable with this name in this scope.")
+                            .with_code("R0001")
+                            .with_label(previous, "previous declaration here"),
+                    );
+                } else {
+                    scope.insert(name.lexeme.clone(), (index, false));
+                }
+            }
+            // redeclaring globals is allowed
+            None => {
+                self.globals.insert(name.lexeme.clone(), index);
+            }
+        }
+
+        index
+    }
+
+    fn define(&mut self, name: &Token, index: usize) {
+        if let Some(scope) = self.scopes.last_mut() {
+            if let Some(entry) = scope.get_mut(&name.lexeme) {
+                *entry = (index, true);
+            }
+        }
+    }
+
+    fn reference(&mut self, name: &Token) {
+        let local = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.lexeme))
+            .map(|(index, _)| *index);
+
+        let declaration = local.or_else(|| self.globals.get(&name.lexeme).copied());
+
+        if declaration.is_none() {
+            self.unresolved.push(self.resolution.references.len());
+        }
+
+        self.resolution.references.push(Reference {
+            name: name.clone(),
+            declaration,
+        });
+    }
+
+    fn error(&mut self, diagnostic: Diagnostic) {
+        errors::emit(diagnostic);
+        self.had_error = true;
+    }
+}
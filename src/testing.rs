@@ -0,0 +1,118 @@
+//! Finding and running the tests of scripts, for `yayarlox test`
+//!
+//! Tests are `test "name" { ... }` blocks and functions named `test_...`
+//! without parameters, at the top level of a file. The rest of the file
+//! runs before each test, in a fresh interpreter, so tests can't affect
+//! one another.
+use crate::ast::{Expr, Stmt};
+use crate::interpreter::{Interpreter, Interrupt};
+use crate::lexer::{Token, TokenVariant};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+pub struct Test {
+    pub name: String,
+    pub line: usize,
+    body: Body,
+}
+
+enum Body {
+    Block(Vec<Stmt>),
+    // the name of the function to call
+    Function(Token),
+}
+
+/// The statements run before each test, and the tests, in order
+pub fn split(statements: Vec<Stmt>) -> (Vec<Stmt>, Vec<Test>) {
+    let (mut setup, mut tests) = (Vec::new(), Vec::new());
+
+    for stmt in statements {
+        match stmt {
+            Stmt::Test(keyword, name, body) => {
+                let name = match name.class {
+                    TokenVariant::String(name) => name,
+                    _ => name.lexeme,
+                };
+
+                tests.push(Test {
+                    name,
+                    line: keyword.line,
+                    body: Body::Block(*body),
+                });
+            }
+            Stmt::Function(ref name, ref params, _)
+                if name.lexeme.starts_with("test_") && params.is_empty() =>
+            {
+                tests.push(Test {
+                    name: name.lexeme.clone(),
+                    line: name.line,
+                    body: Body::Function((**name).clone()),
+                });
+
+                setup.push(stmt);
+            }
+            stmt => setup.push(stmt),
+        }
+    }
+
+    (setup, tests)
+}
+
+/// Runs the setup then the test, failing as the first error interrupts it.
+/// Both are a single run, counted as one against the limits.
+pub fn run(test: &Test, setup: &[Stmt], interpreter: &mut Interpreter) -> Result<(), Interrupt> {
+    let mut statements = setup.to_vec();
+
+    // calls are located at the declaration, for tracebacks
+    let test = match &test.body {
+        Body::Block(body) => Stmt::Block(Box::new(body.clone())),
+        Body::Function(name) => Stmt::Expression(Box::new(Expr::Call(
+            Box::new(Expr::Variable(Box::new(name.clone()))),
+            Box::new(name.clone()),
+            Box::default(),
+        ))),
+    };
+
+    statements.push(test);
+    interpreter.interpret_statements(statements)?;
+
+    Ok(())
+}
+
+/// The `.lox` files among the paths and in the directories, searched
+/// recursively, sorted so that tests always run in the same order.
+/// Links to directories aren't followed, as they could make a loop.
+pub fn discover(paths: &[String]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        let path = Path::new(path);
+
+        if path.is_dir() {
+            search(path, &mut files)?;
+        } else {
+            // named explicitly, so a missing file is reported on reading it
+            files.push(path.to_path_buf());
+        }
+    }
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+fn search(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            search(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use common::{script, stderr, stdout, TempDir};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn resolver_errors_dont_stop_scripts() {
+    let code = "{ var a = 1; var a = 2; print a; }\n";
+
+    let output = script("redeclared", code, &["{}"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "2\n");
+
+    let output = script("redeclared-lint", code, &["lint", "{}"]);
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[R0001]"));
+}
+
+#[test]
+fn debugger_shows_lines_of_imported_files() {
+    let directory = TempDir::new("debug");
+    let directory = directory.path();
+    fs::write(
+        directory.join("lib.lox"),
+        "fun twice(x) {\n    print x * 2;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        directory.join("main.lox"),
+        "import \"lib.lox\";\n\ntwice(3);\n",
+    )
+    .unwrap();
+
+    let mut debugger = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("debug")
+        .arg(directory.join("main.lox"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    debugger
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"s\ns\ns\nl\nc\n")
+        .unwrap();
+
+    let output = debugger.wait_with_output().unwrap();
+
+    assert_eq!(
+        stdout(&output),
+        "[line 1] import \"lib.lox\";\n\
+         (debug) [line 1] fun twice(x) {\n\
+         (debug) [line 3] twice(3);\n\
+         (debug) [line 2] print x * 2;\n\
+         (debug)       1 fun twice(x) {\n\
+         ->    2     print x * 2;\n      3 }\n\
+         (debug) 6\n"
+    );
+}
+
+#[test]
+fn repl_leaves_blocks_on_errors() {
+    let mut repl = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    repl.stdin
+        .take()
+        .unwrap()
+        .write_all(b"{ var a = 1; nil(); }\nprint a;\n")
+        .unwrap();
+
+    // stdin is closed once written, the REPL must stop there
+    let output = repl.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let errors = stderr(&output);
+    assert!(errors.contains("Error[E0002]"));
+    assert!(errors.contains("Error[E0001] at 'a': Variable 'a' doesn't exist."));
+    assert!(!stdout(&output).contains('1'));
+}
+
+#[test]
+fn repl_quotes_the_lines_functions_come_from() {
+    let mut repl = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    repl.stdin
+        .take()
+        .unwrap()
+        .write_all(b"fun f() { print nil + 1; }\nprint 2 + nil;\nprint 3;\nf();\n")
+        .unwrap();
+
+    let output = repl.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let errors = stderr(&output);
+    assert!(errors.contains("1 | print 2 + nil;\n"), "{}", errors);
+    assert!(
+        errors.contains("1 | fun f() { print nil + 1; }\n"),
+        "{}",
+        errors
+    );
+    assert!(stdout(&output).contains('3'));
+}
+
+#[test]
+fn scripts_get_the_arguments_after_their_path() {
+    let output = script(
+        "args",
+        "var a = args();\nprint len(a);\nfor (var i = 0; i < len(a); i = i + 1) print get(a, i);",
+        &["{}", "one", "two words", "--three"],
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "3\none\ntwo words\n--three\n");
+
+    let output = script("no-args", "print len(args());", &["{}"]);
+    assert_eq!(stdout(&output), "0\n");
+}
+
+#[test]
+fn scripts_read_the_environment() {
+    let directory = TempDir::new("getenv");
+    let path = directory.path().join("script.lox");
+    fs::write(
+        &path,
+        "print getenv(\"YAYARLOX_TEST\");\nprint getenv(\"YAYARLOX_UNSET\");",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg(&path)
+        .env("YAYARLOX_TEST", "été")
+        .env_remove("YAYARLOX_UNSET")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "été\nnil\n");
+}
+
+#[test]
+fn exit_sets_the_status() {
+    let output = script(
+        "exit",
+        "print \"before\";\nexit(3);\nprint \"after\";",
+        &["{}"],
+    );
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output), "before\n");
+
+    let output = script("exit-zero", "exit(0);\nprint \"after\";", &["{}"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+
+    // from within calls and blocks too
+    let output = script("exit-nested", "fun f() { { exit(7); } }\nf();", &["{}"]);
+    assert_eq!(output.status.code(), Some(7));
+
+    let output = script("exit-fraction", "exit(1.5);", &["{}"]);
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[E0005]"));
+}
+
+#[test]
+fn shebang_lines_are_skipped() {
+    let output = script(
+        "shebang",
+        "#!/usr/bin/env yayarlox\nprint \"ran\";\nprint nil + 1;\n",
+        &["{}"],
+    );
+
+    assert_eq!(stdout(&output), "ran\n");
+    // lines are still counted from the shebang
+    assert!(stderr(&output).contains("3 | print nil + 1;"));
+}
+
+#[test]
+fn scripts_named_like_subcommands() {
+    let directory = TempDir::new("subcommand-names");
+    fs::write(directory.path().join("lint"), "print args();").unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+            .current_dir(directory.path())
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    for args in [&["run", "lint", "a"], &["--", "lint", "a"]].iter() {
+        let output = run(&args[..]);
+
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(stdout(&output), "[\"a\"]\n");
+    }
+
+    // options still come first
+    let output = run(&["--sandbox", "--", "lint"]);
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[E0018]"));
+
+    // otherwise it's the subcommand
+    let output = run(&["lint", "lint"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "");
+}
@@ -0,0 +1,114 @@
+//! Helpers shared by the integration tests, each test file using some
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use yayarlox::{errors, lex_and_parse, types, Interpreter, Interrupt};
+
+/// Runs code, which must parse, in the interpreter
+pub fn run(interpreter: &mut Interpreter, code: &str) -> Result<types, Interrupt> {
+    let statements = lex_and_parse("<test>", String::from(code))
+        .unwrap_or_else(|summary| panic!("{}\n{}", summary, code));
+
+    interpreter.interpret(statements)
+}
+
+/// Runs code which must succeed
+pub fn exec(interpreter: &mut Interpreter, code: &str) {
+    if let Err(interrupt) = run(interpreter, code) {
+        panic!("{}\nfailed: {}", code, describe(&interrupt));
+    }
+}
+
+/// Value of an expression, in the global scope
+pub fn value(interpreter: &mut Interpreter, expression: &str) -> types {
+    exec(interpreter, &format!("var result__ = {};", expression));
+    interpreter.get_global("result__").unwrap()
+}
+
+/// Code of the error the code fails with, which it must
+pub fn error_code(interpreter: &mut Interpreter, code: &str) -> String {
+    match errors::capture(|| run(interpreter, code)).0 {
+        Ok(value) => panic!("{} succeeded with {:?}", code, value),
+        Err(Interrupt::Error(error)) => error.diagnostic.code.unwrap_or_default(),
+        Err(Interrupt::Exit(status)) => panic!("{} exited with {}", code, status),
+    }
+}
+
+/// Message of the error the code fails with, which it must
+pub fn error_message(interpreter: &mut Interpreter, code: &str) -> String {
+    match errors::capture(|| run(interpreter, code)).0 {
+        Ok(value) => panic!("{} succeeded with {:?}", code, value),
+        Err(Interrupt::Error(error)) => error.diagnostic.message,
+        Err(Interrupt::Exit(status)) => panic!("{} exited with {}", code, status),
+    }
+}
+
+fn describe(interrupt: &Interrupt) -> String {
+    match interrupt {
+        Interrupt::Error(error) => format!(
+            "{} {}",
+            error.diagnostic.code.as_deref().unwrap_or(""),
+            error.diagnostic.message
+        ),
+        Interrupt::Exit(status) => format!("exit {}", status),
+    }
+}
+
+pub fn string(text: &str) -> types {
+    types::string(String::from(text))
+}
+
+/// Directory of its own for a test, empty, removed when dropped
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("yayarlox-{}-{}", name, std::process::id()));
+
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        TempDir(path.canonicalize().unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// The path of a file in it, as a Lox string literal
+    pub fn literal(&self, name: &str) -> String {
+        format!("\"{}\"", self.0.join(name).display())
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Runs the binary with the arguments, `{}` in them replaced
+/// by the path of a script holding `code`
+pub fn script(name: &str, code: &str, args: &[&str]) -> Output {
+    let directory = TempDir::new(name);
+    let path = directory.path().join("script.lox");
+    fs::write(&path, code).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .args(
+            args.iter()
+                .map(|arg| arg.replace("{}", &path.display().to_string())),
+        )
+        .output()
+        .unwrap()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{exec, run, string, value};
+use yayarlox::{errors, lex_and_parse, types, Interpreter, Interrupt};
+
+#[test]
+fn natives_globals_and_calls_from_the_host() {
+    let mut interpreter = Interpreter::builder().build();
+
+    interpreter.define_native("double", 1, |arguments| match arguments[0] {
+        types::number(value) => Ok(types::number(value * 2.0)),
+        _ => Err(String::from("Argument must be a number.")),
+    });
+    interpreter.set_global("limit", types::number(21.0));
+
+    exec(
+        &mut interpreter,
+        "fun greet(name) { return_ = \"Hi \" + name + \" \" + str(double(limit)); }\nvar return_;",
+    );
+
+    let greet = interpreter.get_global("greet").unwrap();
+    assert!(interpreter.call(greet, vec![string("host")]).is_ok());
+    assert_eq!(value(&mut interpreter, "return_"), string("Hi host 42"));
+
+    match errors::capture(|| run(&mut interpreter, "double(nil);")).0 {
+        Err(Interrupt::Error(error)) => {
+            assert_eq!(error.diagnostic.message, "Argument must be a number.")
+        }
+        _ => panic!("the native didn't fail"),
+    }
+}
+
+#[test]
+fn exit_is_an_interrupt() {
+    let mut interpreter = Interpreter::new(Vec::new());
+
+    assert!(matches!(
+        run(&mut interpreter, "exit(3);"),
+        Err(Interrupt::Exit(3))
+    ));
+}
+
+fn runtime_error(code: &str) -> yayarlox::RuntimeError {
+    let statements = lex_and_parse("<embedded>", String::from(code))
+        .ok()
+        .unwrap();
+
+    match Interpreter::builder().build().interpret(statements) {
+        Err(Interrupt::Error(error)) => *error,
+        _ => panic!("{} didn't fail", code),
+    }
+}
+
+#[test]
+fn sources_are_quoted_until_cleared() {
+    let error = runtime_error("var a = 1;\nprint a + nil;\n");
+
+    assert!(errors::render(&error.diagnostic).contains("2 | print a + nil;"));
+
+    errors::clear_sources();
+
+    let rendered = errors::render(&error.diagnostic);
+    assert!(rendered.contains("Error[E0004]"));
+    assert!(!rendered.contains("print a + nil"));
+
+    // new code doesn't take the place of the code forgotten
+    let newer = runtime_error("print nil + 1;\n");
+    assert!(!errors::render(&error.diagnostic).contains("print nil + 1"));
+    assert!(errors::render(&newer.diagnostic).contains("1 | print nil + 1;"));
+}
+
+#[test]
+fn failed_parses_are_reported_and_forgotten() {
+    let (result, diagnostics) =
+        errors::capture(|| lex_and_parse("<embedded>", String::from("print ;")));
+
+    assert_eq!(result.err(), Some("Aborting due to error while parsing."));
+    assert_eq!(diagnostics[0].code.as_deref(), Some("P0001"));
+    assert!(!errors::render(&diagnostics[0]).contains("-->"));
+}
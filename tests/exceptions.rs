@@ -0,0 +1,133 @@
+mod common;
+
+use common::{error_code, error_message, exec, string, value};
+use yayarlox::{types, Interpreter};
+
+fn interpreter() -> Interpreter {
+    Interpreter::builder().build()
+}
+
+#[test]
+fn thrown_values_are_caught() {
+    let mut interpreter = interpreter();
+
+    exec(
+        &mut interpreter,
+        "var caught; try { throw \"boom\"; } catch (e) { caught = e; }",
+    );
+
+    assert_eq!(value(&mut interpreter, "caught"), string("boom"));
+}
+
+#[test]
+fn runtime_errors_are_caught_as_error_values() {
+    let mut interpreter = interpreter();
+
+    exec(
+        &mut interpreter,
+        "var e;\ntry {\n    nil();\n} catch (error) {\n    e = error;\n}",
+    );
+
+    assert_eq!(value(&mut interpreter, "type(e)"), string("error"));
+    assert_eq!(
+        value(&mut interpreter, "errorMessage(e)"),
+        string("Can only call functions and classes.")
+    );
+    assert_eq!(value(&mut interpreter, "errorLine(e)"), types::number(3.0));
+}
+
+#[test]
+fn finally_runs_on_every_path() {
+    let mut interpreter = interpreter();
+
+    exec(
+        &mut interpreter,
+        r#"
+var log = "";
+try { log = log + "a"; } finally { log = log + "1"; }
+try { throw 0; } catch (e) { log = log + "b"; } finally { log = log + "2"; }
+try {
+    try { throw 0; } finally { log = log + "3"; }
+} catch (e) {
+    log = log + "c";
+}
+try {
+    try { throw 0; } catch (e) { throw 1; } finally { log = log + "4"; }
+} catch (e) {
+    log = log + str(e);
+}
+"#,
+    );
+
+    assert_eq!(value(&mut interpreter, "log"), string("a1b23c41"));
+}
+
+#[test]
+fn errors_unwind_through_calls_and_loops() {
+    let mut interpreter = interpreter();
+
+    exec(
+        &mut interpreter,
+        r#"
+var log = "";
+fun inner(n) {
+    try {
+        if (n == 0) throw "bottom";
+        inner(n - 1);
+    } finally {
+        log = log + str(n);
+    }
+}
+var i = 0;
+try {
+    while (true) {
+        i = i + 1;
+        if (i == 3) inner(2);
+    }
+} catch (e) {
+    log = log + e;
+}
+"#,
+    );
+
+    assert_eq!(value(&mut interpreter, "log"), string("012bottom"));
+    assert_eq!(value(&mut interpreter, "i"), types::number(3.0));
+}
+
+#[test]
+fn catch_scopes_are_left() {
+    let mut interpreter = interpreter();
+
+    exec(
+        &mut interpreter,
+        "var e = \"outer\"; try { var local = 1; throw 0; } catch (e) {}",
+    );
+
+    assert_eq!(value(&mut interpreter, "e"), string("outer"));
+    assert_eq!(error_code(&mut interpreter, "print local;"), "E0001");
+}
+
+#[test]
+fn uncaught_throws_are_errors() {
+    let mut interpreter = interpreter();
+
+    assert_eq!(error_code(&mut interpreter, "throw \"up\";"), "E0008");
+    assert_eq!(
+        error_message(&mut interpreter, "try { throw 1; } finally {}"),
+        "Uncaught exception: 1"
+    );
+}
+
+#[test]
+fn limits_and_exits_are_not_caught() {
+    let mut interpreter = interpreter();
+    interpreter.limits.steps = Some(1000);
+
+    assert_eq!(
+        error_code(
+            &mut interpreter,
+            "try { while (true) {} } catch (e) { print \"caught\"; }"
+        ),
+        "E0014"
+    );
+}
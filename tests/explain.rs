@@ -0,0 +1,148 @@
+mod common;
+
+use common::{script, stderr, stdout};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+// Codes whose examples aren't a whole script: elided code,
+// several files or a command line
+const ILLUSTRATIVE: [&str; 6] = ["E0006", "E0010", "E0011", "E0012", "P0005", "P0007"];
+
+fn explain(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("explain")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Codes listed by `yayarlox explain`
+fn catalogue() -> BTreeSet<String> {
+    let output = explain(&[]);
+    assert!(output.status.success());
+
+    stdout(&output)
+        .lines()
+        .map(|line| String::from(line.split("  ").next().unwrap()))
+        .collect()
+}
+
+/// Quoted diagnostic codes, like `"E0004"`, in the sources of the directory
+fn codes_in(directory: &Path, codes: &mut BTreeSet<String>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            codes_in(&path, codes);
+        } else if path.extension() == Some("rs".as_ref()) {
+            let text = fs::read_to_string(&path).unwrap();
+
+            for quoted in text.as_bytes().windows(7) {
+                if quoted[0] == b'"'
+                    && quoted[6] == b'"'
+                    && b"LPREW".contains(&quoted[1])
+                    && quoted[2..6].iter().all(u8::is_ascii_digit)
+                {
+                    codes.insert(String::from_utf8(quoted[1..6].to_vec()).unwrap());
+                }
+            }
+        }
+    }
+}
+
+/// The erroneous code example of an explanation, unindented
+fn example(explanation: &str) -> Option<String> {
+    let (_, rest) = explanation.split_once("\nErroneous code example:\n\n")?;
+    let (example, _) = rest.split_once("\n\nFix:")?;
+
+    Some(
+        example
+            .lines()
+            .map(|line| line.strip_prefix("    ").unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+#[test]
+fn every_code_reported_is_explained() {
+    let mut used = BTreeSet::new();
+    codes_in(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+        &mut used,
+    );
+
+    assert!(used.len() > 40, "{:?}", used);
+    assert_eq!(catalogue(), used);
+}
+
+#[test]
+fn explanations_have_a_title_an_example_and_a_fix() {
+    let output = explain(&["L0003"]);
+    let printed = stdout(&output);
+
+    assert!(output.status.success());
+    assert!(
+        printed.starts_with("L0003: unterminated string\n\n"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("\nErroneous code example:\n\n    "));
+    assert!(printed.contains("\nFix:\n\n    "));
+
+    // codes aren't case sensitive
+    assert_eq!(stdout(&explain(&["l0003"])), printed);
+}
+
+#[test]
+fn examples_report_their_code() {
+    for code in catalogue() {
+        let printed = stdout(&explain(&[&code]));
+        let example = match example(&printed) {
+            Some(example) => example,
+            None => continue,
+        };
+
+        if ILLUSTRATIVE.contains(&&*code) {
+            continue;
+        }
+
+        // limits low enough for the examples exceeding them to end quickly
+        let mut args = vec![
+            "--max-steps=100000",
+            "--max-time=2",
+            "--max-depth=100",
+            "--max-string-bytes=100000",
+        ];
+        match &code[..1] {
+            // resolution errors and lints are only reported by the linter,
+            // comparisons of numbers with strings are checked at runtime
+            "R" | "W" if code != "W0009" => args.push("lint"),
+            _ if code == "E0018" => args.push("--sandbox"),
+            _ => args.push("run"),
+        }
+        args.push("{}");
+
+        let output = script("explain-examples", &example, &args);
+        assert!(
+            stderr(&output).contains(&format!("[{}]", code)),
+            "{}:\n{}\n{}",
+            code,
+            example,
+            stderr(&output)
+        );
+    }
+}
+
+#[test]
+fn unknown_codes_are_refused() {
+    let output = explain(&["E9999"]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("No error code 'E9999'"));
+
+    assert_eq!(explain(&["E0001", "E0002"]).status.code(), Some(64));
+}
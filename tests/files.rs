@@ -0,0 +1,114 @@
+mod common;
+
+use common::{error_code, error_message, exec, string, value, TempDir};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use yayarlox::{types, Interpreter};
+
+#[test]
+fn writing_appending_and_reading() {
+    let directory = TempDir::new("files-write");
+    let mut interpreter = Interpreter::builder().allow_all().build();
+    let path = directory.literal("notes.txt");
+
+    exec(
+        &mut interpreter,
+        &format!(
+            "writeFile({0}, \"été \"); appendFile({0}, \"hiver\"); var text = readFile({0});",
+            path
+        ),
+    );
+
+    assert_eq!(value(&mut interpreter, "text"), string("été hiver"));
+    assert_eq!(
+        fs::read_to_string(directory.path().join("notes.txt")).unwrap(),
+        "été hiver"
+    );
+}
+
+#[test]
+fn existence_and_listing() {
+    let directory = TempDir::new("files-list");
+    fs::write(directory.path().join("b.lox"), "").unwrap();
+    fs::write(directory.path().join("a.txt"), "").unwrap();
+    fs::create_dir(directory.path().join("sub")).unwrap();
+
+    let mut interpreter = Interpreter::builder().allow_all().build();
+    let literal = format!("\"{}\"", directory.path().display());
+
+    assert_eq!(
+        value(&mut interpreter, &format!("listDir({})", literal)),
+        types::list(vec![string("a.txt"), string("b.lox"), string("sub")])
+    );
+    assert_eq!(
+        value(
+            &mut interpreter,
+            &format!("fileExists({})", directory.literal("sub"))
+        ),
+        types::boolean(true)
+    );
+    assert_eq!(
+        value(
+            &mut interpreter,
+            &format!("fileExists({})", directory.literal("nothing"))
+        ),
+        types::boolean(false)
+    );
+}
+
+#[test]
+fn failures_name_the_path() {
+    let directory = TempDir::new("files-fail");
+    let mut interpreter = Interpreter::builder().allow_all().build();
+
+    let missing = directory.literal("missing.txt");
+    let code = format!("readFile({});", missing);
+
+    assert_eq!(error_code(&mut interpreter, &code), "E0009");
+    assert!(error_message(&mut interpreter, &code).starts_with(&format!(
+        "Couldn't read '{}': ",
+        directory.path().join("missing.txt").display()
+    )));
+
+    let code = format!("writeFile({}, \"x\");", directory.literal("no/such/dir"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0009");
+
+    let code = format!("listDir({});", missing);
+    assert_eq!(error_code(&mut interpreter, &code), "E0009");
+
+    assert_eq!(error_code(&mut interpreter, "readFile(1);"), "E0005");
+}
+
+#[test]
+fn reading_the_standard_input() {
+    let directory = TempDir::new("files-stdin");
+    let path = directory.path().join("script.lox");
+    fs::write(
+        &path,
+        "print readLine();\nprint input(\"? \");\nprint readLine();\n",
+    )
+    .unwrap();
+
+    let mut script = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    script
+        .stdin
+        .take()
+        .unwrap()
+        .write_all("première\nsecond".as_bytes())
+        .unwrap();
+
+    let output = script.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "première\n? second\nnil\n"
+    );
+}
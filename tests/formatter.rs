@@ -0,0 +1,62 @@
+use std::fs;
+use std::process::Command;
+
+/// Formats `code` with the binary, through a file as `fmt` works in place
+fn format(name: &str, code: &str) -> String {
+    let path =
+        std::env::temp_dir().join(format!("yayarlox-fmt-{}-{}.lox", std::process::id(), name));
+    fs::write(&path, code).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("fmt")
+        .arg(&path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let formatted = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    formatted
+}
+
+fn assert_formats(name: &str, code: &str, expected: &str) {
+    let formatted = format(name, code);
+    assert_eq!(formatted, expected);
+    assert_eq!(format(name, &formatted), expected, "not idempotent");
+}
+
+#[test]
+fn else_if_chains_stay_level() {
+    assert_formats(
+        "else-if",
+        "if (a) {\nprint 1;\n} else if (b) {\nprint 2;\n}\nelse if (c) { print 3; } else {\nprint 4;\n}\n",
+        "if (a) {\n    print 1;\n} else if (b) {\n    print 2;\n} else if (c) {\n    print 3;\n} else {\n    print 4;\n}\n",
+    );
+}
+
+#[test]
+fn else_if_chains_without_braces() {
+    assert_formats(
+        "else-if-bare",
+        "if (a) print 1; else if (b) print 2; else if (c)\nprint 3; else print 4;\nprint 5;\n",
+        "if (a) print 1;\nelse if (b) print 2;\nelse if (c)\n    print 3;\nelse print 4;\nprint 5;\n",
+    );
+}
+
+#[test]
+fn empty_blocks() {
+    assert_formats(
+        "empty",
+        "{ }\n{\n\n}\nfun f() {\n}\nif (a) { } else {\n}\nwhile (x) {}\n",
+        "{}\n{}\nfun f() {}\nif (a) {} else {}\nwhile (x) {}\n",
+    );
+}
+
+#[test]
+fn nested_bodies() {
+    assert_formats(
+        "nested",
+        "fun f(n) {\nif (n > 0)\nif (n > 1) print 2; else print 1;\nprint 0;\n}\n",
+        "fun f(n) {\n    if (n > 0)\n        if (n > 1) print 2;\n        else print 1;\n    print 0;\n}\n",
+    );
+}
@@ -0,0 +1,205 @@
+//! Random inputs for the lexer and the parser, which must report
+//! errors rather than panic or overflow the stack, whatever they're given
+use std::thread;
+use yayarlox::{errors, lex_and_parse, Interpreter};
+
+// what a host's main thread usually gets
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+const TOKENS: &[&str] = &[
+    "(", ")", "{", "}", ",", ".", "-", "+", ";", "/", "*", "!", "!=", "=", "==", ">", ">=", "<",
+    "<=", "a", "b", "f", "\"s\"", "\"é\"", "1", "2.5", "and", "class", "else", "false", "fun",
+    "for", "if", "nil", "or", "print", "return", "super", "this", "true", "var", "while", "try",
+    "catch", "finally", "throw", "import", "as", "test", "debugger", "//", "\n", "\"", "@", "€",
+];
+
+/// xorshift, to get the same inputs on every run
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn token_soup(&mut self) -> String {
+        let length = self.below(60);
+        (0..length)
+            .map(|_| self.pick(TOKENS))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn expression(&mut self, depth: usize) -> String {
+        if depth == 0 {
+            return String::from(self.pick(&["1", "a", "\"s\"", "nil", "true"]));
+        }
+
+        match self.below(6) {
+            0 => format!("({})", self.expression(depth - 1)),
+            1 => format!("{}{}", self.pick(&["-", "!"]), self.expression(depth - 1)),
+            2 => format!(
+                "f({}, {})",
+                self.expression(depth - 1),
+                self.expression(depth - 1)
+            ),
+            3 => format!("(a = {})", self.expression(depth - 1)),
+            _ => format!(
+                "{} {} {}",
+                self.expression(depth - 1),
+                self.pick(&["+", "-", "*", "/", "==", "<", "and", "or"]),
+                self.expression(depth - 1)
+            ),
+        }
+    }
+
+    fn statement(&mut self, depth: usize) -> String {
+        if depth == 0 {
+            return format!("print {};", self.expression(3));
+        }
+
+        match self.below(6) {
+            0 => format!("{{ {} }}", self.statement(depth - 1)),
+            1 => format!(
+                "if ({}) {} else {}",
+                self.expression(2),
+                self.statement(depth - 1),
+                self.statement(depth - 1)
+            ),
+            2 => format!(
+                "while ({}) {}",
+                self.expression(2),
+                self.statement(depth - 1)
+            ),
+            3 => format!("{{ fun f(a) {{ {} }} }}", self.statement(depth - 1)),
+            4 => format!("{{ var a = {}; }}", self.expression(depth)),
+            _ => format!(
+                "try {{ {} }} catch (e) {{ {} }}",
+                self.statement(depth - 1),
+                self.statement(depth - 1)
+            ),
+        }
+    }
+
+    /// Valid code with a few characters dropped or duplicated
+    fn mutated(&mut self, code: &str) -> String {
+        let mut characters: Vec<char> = code.chars().collect();
+
+        for _ in 0..self.below(4) + 1 {
+            if characters.is_empty() {
+                break;
+            }
+
+            let index = self.below(characters.len());
+            if self.below(2) == 0 {
+                characters.remove(index);
+            } else {
+                characters.insert(index, characters[index]);
+            }
+        }
+
+        characters.into_iter().collect()
+    }
+}
+
+/// Parses on a thread with a main thread's stack, reporting nothing
+fn parses(code: String) -> bool {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || errors::capture(|| lex_and_parse("fuzz", code).is_ok()).0)
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+/// Parses and runs with the default limits, on a main thread's stack
+fn runs(code: String) -> Option<bool> {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            errors::capture(|| {
+                let statements = lex_and_parse("fuzz", code).ok()?;
+                Some(Interpreter::builder().build().interpret(statements).is_ok())
+            })
+            .0
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+#[test]
+fn token_soup() {
+    let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..2000 {
+        parses(random.token_soup());
+    }
+}
+
+#[test]
+fn generated_programs_parse() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..300 {
+        let code = random.statement(4);
+        assert!(parses(code.clone()), "{}", code);
+    }
+}
+
+#[test]
+fn mutated_programs() {
+    let mut random = Random(0xdead_beef_cafe_f00d);
+    let example = include_str!("../example.lox");
+
+    for _ in 0..300 {
+        parses(random.mutated(example));
+    }
+}
+
+#[test]
+fn long_chains_are_rejected() {
+    for operator in ["+", "*", "==", "and", "or"].iter() {
+        let code = format!("print {};", vec!["1"; 5000].join(operator));
+        assert_eq!(runs(code), None);
+    }
+
+    assert_eq!(runs(format!("print clock{};", "()".repeat(5000))), None);
+    assert_eq!(runs(format!("var a; {}1;", "a = ".repeat(5000))), None);
+    assert_eq!(runs(format!("print {}1;", "-".repeat(5000))), None);
+}
+
+#[test]
+fn nesting_up_to_the_limit_runs() {
+    let code = format!("print {};", vec!["1"; 150].join(" + "));
+    assert_eq!(runs(code), Some(true));
+
+    // chains have their own limit, which holds at the deepest nesting
+    for operator in [" + ", " and ", " == "].iter() {
+        let chain = vec!["1"; 390].join(operator);
+        assert_eq!(runs(format!("print {};", chain)), Some(true));
+
+        let code = format!("print {}{}{};", "(".repeat(190), chain, ")".repeat(190));
+        assert_eq!(runs(code), Some(true));
+    }
+
+    let code = format!("print {}1{};", "(".repeat(150), ")".repeat(150));
+    assert_eq!(runs(code), Some(true));
+
+    let code = format!("{}print 1;{}", "{".repeat(150), "}".repeat(150));
+    assert_eq!(runs(code), Some(true));
+}
+
+#[test]
+fn deep_nesting_is_rejected() {
+    for (open, close) in [("(", ")"), ("{", "}"), ("[", "]")].iter() {
+        let code = format!("print {}1{};", open.repeat(100_000), close.repeat(100_000));
+        assert!(!parses(code));
+    }
+}
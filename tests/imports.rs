@@ -0,0 +1,74 @@
+mod common;
+
+use common::{error_code, error_message, exec, run, string, value, TempDir};
+use std::fs;
+use yayarlox::{errors, Interpreter};
+
+#[test]
+fn modules_and_relative_paths() {
+    let directory = TempDir::new("imports-modules");
+    fs::create_dir(directory.path().join("lib")).unwrap();
+    fs::write(
+        directory.path().join("lib/util.lox"),
+        "import \"names.lox\" as names;\nfun greet() { return_ = \"Hi \" + names.name; }\nvar return_;",
+    )
+    .unwrap();
+    fs::write(
+        directory.path().join("lib/names.lox"),
+        "var name = \"lib\";",
+    )
+    .unwrap();
+
+    let mut interpreter = Interpreter::builder().allow_all().build();
+
+    exec(
+        &mut interpreter,
+        &format!(
+            "import {} as util;\nutil.greet();\nvar greeting = util.return_;",
+            directory.literal("lib/util.lox")
+        ),
+    );
+
+    assert_eq!(value(&mut interpreter, "greeting"), string("Hi lib"));
+}
+
+#[test]
+fn invalid_paths() {
+    let mut interpreter = Interpreter::builder().allow_all().build();
+
+    assert_eq!(error_code(&mut interpreter, "import \"\";"), "E0019");
+}
+
+#[test]
+fn missing_and_unreadable_files() {
+    let directory = TempDir::new("imports-missing");
+    fs::create_dir(directory.path().join("folder.lox")).unwrap();
+    let mut interpreter = Interpreter::builder().allow_all().build();
+
+    let code = format!("import {};", directory.literal("missing.lox"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0020");
+    assert!(error_message(&mut interpreter, &code).starts_with(&format!(
+        "Couldn't import '{}': ",
+        directory.path().join("missing.lox").display()
+    )));
+
+    let code = format!("import {};", directory.literal("folder.lox"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0020");
+}
+
+#[test]
+fn cycles_and_files_with_errors() {
+    let directory = TempDir::new("imports-cycles");
+    fs::write(directory.path().join("a.lox"), "import \"b.lox\";").unwrap();
+    fs::write(directory.path().join("b.lox"), "import \"a.lox\";").unwrap();
+    fs::write(directory.path().join("broken.lox"), "print ;").unwrap();
+    let mut interpreter = Interpreter::builder().allow_all().build();
+
+    let code = format!("import {};", directory.literal("a.lox"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0010");
+
+    let code = format!("import {};", directory.literal("broken.lox"));
+    let (_, diagnostics) = errors::capture(|| run(&mut interpreter, &code));
+    assert_eq!(diagnostics[0].code.as_deref(), Some("P0001"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0011");
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{stderr, stdout, TempDir};
+use std::fs;
+use std::process::{Command, Output};
+
+/// Runs the code with `--error-format=json` and the arguments,
+/// the path of the script in what's printed replaced by `FILE`
+fn json(name: &str, code: &str, args: &[&str]) -> (Output, Vec<String>) {
+    let directory = TempDir::new(name);
+    let path = directory.path().join("script.lox");
+    fs::write(&path, code).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("--error-format=json")
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    let lines = stderr(&output)
+        .lines()
+        .map(|line| line.replace(&path.display().to_string(), "FILE"))
+        .collect();
+
+    (output, lines)
+}
+
+#[test]
+fn runtime_errors_with_related_spans_and_notes() {
+    let (output, lines) = json("json-runtime", "var a = 1;\nprint a + nil;\n", &[]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        lines,
+        [concat!(
+            r#"{"severity":"error","code":"E0004","message":"Operands must be two numbers or two strings","#,
+            r#""file":"FILE","line":2,"column":9,"span":{"start":19,"end":20},"#,
+            r#""related":[{"message":"number","file":"FILE","line":2,"column":7,"span":{"start":17,"end":18}},"#,
+            r#"{"message":"nil","file":"FILE","line":2,"column":11,"span":{"start":21,"end":24}}],"#,
+            r#""notes":["in script [line 2]"]}"#
+        )]
+    );
+}
+
+#[test]
+fn one_object_per_line_without_summaries() {
+    let (output, lines) = json("json-parse", "var = 1;\nvar = 2;\n", &[]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(lines[0].starts_with(r#"{"severity":"error","code":"P0004","#));
+    assert!(lines[1].contains(r#""line":2,"#));
+    assert!(lines
+        .iter()
+        .all(|line| line.starts_with('{') && line.ends_with('}')));
+}
+
+#[test]
+fn warnings_go_with_the_output_of_the_script() {
+    let (output, lines) = json("json-warning", "print 1 == \"1\";\n", &[]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "false\n");
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with(concat!(
+        r#"{"severity":"warning","code":"W0009","#,
+        r#""message":"Comparison of a number with a string is always false.","#
+    )));
+
+    let (output, lines) = json("json-lint", "{ var a = 1; }\n", &["lint"]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        lines,
+        [concat!(
+            r#"{"severity":"warning","code":"W0001","message":"Unused variable 'a'.","#,
+            r#""file":"FILE","line":1,"column":7,"span":{"start":6,"end":7},"related":[],"notes":[]}"#
+        )]
+    );
+}
+
+#[test]
+fn strings_are_escaped() {
+    let (_, lines) = json("json-escapes", "throw \"tab\there \\ é\";\n", &[]);
+
+    assert_eq!(lines.len(), 1);
+    assert!(
+        lines[0].contains(r#""message":"Uncaught exception: tab\there \\ é""#),
+        "{}",
+        lines[0]
+    );
+}
+
+#[test]
+fn unknown_formats_are_refused() {
+    let (output, _) = json("json-unknown", "print 1;\n", &["--error-format=xml"]);
+
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(stdout(&output), "");
+}
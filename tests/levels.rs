@@ -0,0 +1,143 @@
+mod common;
+
+use common::{script, stderr, stdout, TempDir};
+use std::fs;
+use std::process::{Command, Output};
+
+const WARNED: &str = "print 1 == \"1\";\nvar a = 1;\nvar a = 2;\nprint 1 / 0;\n";
+
+/// Runs the script with the options, `import {}` in it importing `lib`
+fn with_import(name: &str, lib: &str, code: &str, options: &[&str]) -> Output {
+    let directory = TempDir::new(name);
+    fs::write(directory.path().join("lib.lox"), lib).unwrap();
+
+    let path = directory.path().join("script.lox");
+    let code = code.replace("{}", &directory.literal("lib.lox"));
+    fs::write(&path, code).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .args(options)
+        .arg(&path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn warnings_are_reported_and_the_script_runs() {
+    let output = script("levels-warn", WARNED, &["{}"]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "false\ninf\n");
+
+    let warnings = stderr(&output);
+    for code in ["W0008", "W0009", "W0010"].iter() {
+        assert!(warnings.contains(&format!("Warning[{}]", code)), "{}", code);
+    }
+}
+
+#[test]
+fn deny_warnings_stops_before_running() {
+    let output = script("levels-deny", WARNED, &["--deny-warnings", "{}"]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("Error[W0008]"));
+    assert!(stderr(&output).contains("Aborting due to denied warnings."));
+}
+
+#[test]
+fn levels_from_the_command_line() {
+    let output = script("levels-allow", WARNED, &["--allow=warnings", "{}"]);
+    assert!(output.status.success());
+    assert_eq!(stderr(&output), "");
+
+    // runtime warnings are denied when met
+    let output = script("levels-deny-one", WARNED, &["--deny=W0009", "{}"]);
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[W0009]"));
+    assert_eq!(stdout(&output), "");
+
+    let output = script("levels-unknown", WARNED, &["--deny=nonsense", "{}"]);
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn pragmas_override_the_command_line() {
+    let code = format!(
+        "//! deny(type-mismatch) allow(W0008, division-by-zero)\n{}",
+        WARNED
+    );
+
+    let output = script("levels-pragma", &code, &["--allow=type-mismatch", "{}"]);
+
+    assert_eq!(output.status.code(), Some(65));
+    let errors = stderr(&output);
+    assert!(errors.contains("Error[W0009]"));
+    assert!(!errors.contains("W0008"));
+    assert!(!errors.contains("W0010"));
+}
+
+#[test]
+fn pragmas_only_count_at_the_top() {
+    let code = format!("print 1;\n//! allow(warnings)\n{}", WARNED);
+
+    let output = script("levels-late-pragma", &code, &["{}"]);
+
+    assert!(output.status.success());
+    assert!(stderr(&output).contains("Warning[W0009]"));
+}
+
+#[test]
+fn unknown_lints_in_pragmas_are_warned_about() {
+    let code = "//! warn(nonsense)\nprint 1;\n";
+
+    let output = script("levels-pragma-unknown", code, &["{}"]);
+
+    assert!(output.status.success());
+    assert!(stderr(&output).contains("Warning[W0011]: Unknown lint 'nonsense'."));
+    assert_eq!(stdout(&output), "1\n");
+}
+
+#[test]
+fn imported_files_have_their_own_pragmas() {
+    let output = with_import(
+        "levels-import-deny",
+        "//! deny(division-by-zero)\nvar half = 1 / 0;\n",
+        "import {};\nprint 1 / 0;\n",
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(65));
+    let printed = stderr(&output);
+    assert!(printed.contains("[line 2] Warning[W0010]"), "{}", printed);
+    assert!(printed.contains("[line 2] Error[W0010]"), "{}", printed);
+    assert!(printed.contains("Error[E0011]"), "{}", printed);
+    assert_eq!(stdout(&output), "");
+
+    // those of the importer don't apply to it
+    let output = with_import(
+        "levels-import-own",
+        "var a;\nvar a;\nprint 1 == \"1\";\n",
+        "//! allow(warnings)\nimport {};\nprint 1 == \"1\";\n",
+        &[],
+    );
+
+    assert!(output.status.success());
+    let printed = stderr(&output);
+    assert!(printed.contains("[line 2] Warning[W0008]"), "{}", printed);
+    assert_eq!(printed.matches("Warning[W0009]").count(), 1, "{}", printed);
+    assert_eq!(stdout(&output), "false\nfalse\n");
+}
+
+#[test]
+fn imported_code_runs_with_the_levels_of_its_file() {
+    let lib = "fun compare() {\n    print 1 == \"1\";\n}\n";
+    let code = "//! allow(type-mismatch)\nimport {};\ncompare();\n";
+
+    // even when called from the script allowing it
+    let output = with_import("levels-import-cli", lib, code, &["--deny=type-mismatch"]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("[line 2] Error[W0009]"));
+    assert_eq!(stdout(&output), "");
+}
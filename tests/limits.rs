@@ -0,0 +1,126 @@
+mod common;
+
+use common::{error_code, exec, script, stderr, stdout, value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+use yayarlox::limits::EMBEDDED_DEPTH;
+use yayarlox::{types, Interpreter};
+
+#[test]
+fn runaway_recursion_fits_a_main_thread() {
+    // the size of the stack of a main thread on most systems
+    let code = thread::Builder::new()
+        .stack_size(8 * 1024 * 1024)
+        .spawn(|| {
+            let mut interpreter = Interpreter::builder().build();
+
+            error_code(
+                &mut interpreter,
+                "fun f(n) { if (n > 0) { var x = 1 + (2 * (3 + (f(n - 1) or 1))); } }\nf(1000000);",
+            )
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(code, "E0016");
+}
+
+#[test]
+fn default_depth_is_usable() {
+    let mut interpreter = Interpreter::builder().build();
+
+    exec(
+        &mut interpreter,
+        &format!(
+            "var depth = 0;\nfun f(n) {{ depth = n; if (n > 1) f(n - 1); }}\nf({});",
+            EMBEDDED_DEPTH
+        ),
+    );
+
+    assert_eq!(value(&mut interpreter, "depth"), types::number(1.0));
+}
+
+#[test]
+fn steps_are_counted_per_run() {
+    let mut interpreter = Interpreter::builder().build();
+    interpreter.limits.steps = Some(1000);
+
+    for _ in 0..10 {
+        exec(&mut interpreter, "for (var i = 0; i < 50; i = i + 1) {}");
+    }
+
+    exec(&mut interpreter, "fun spin() { while (true) {} }");
+    assert_eq!(error_code(&mut interpreter, "spin();"), "E0014");
+
+    // the host's calls are runs of their own too
+    let spin = interpreter.get_global("spin").unwrap();
+    let first = interpreter.call(spin.clone(), Vec::new()).is_err();
+    let second = interpreter.call(spin, Vec::new()).is_err();
+    assert!(first && second);
+
+    exec(&mut interpreter, "var fine = 1;");
+}
+
+#[test]
+fn time_is_counted_per_run() {
+    let mut interpreter = Interpreter::builder().build();
+    interpreter.limits.time = Some(Duration::from_millis(100));
+
+    assert_eq!(error_code(&mut interpreter, "while (true) {}"), "E0015");
+    exec(&mut interpreter, "var fine = 1;");
+
+    thread::sleep(Duration::from_millis(150));
+    exec(&mut interpreter, "var still = 1;");
+}
+
+#[test]
+fn string_bytes_are_counted_per_run() {
+    let mut interpreter = Interpreter::builder().build();
+    interpreter.limits.string_bytes = Some(100);
+
+    for _ in 0..10 {
+        exec(&mut interpreter, "var s = \"0123456789\" + \"0123456789\";");
+    }
+
+    assert_eq!(
+        error_code(&mut interpreter, "var s = \"a\"; while (true) s = s + s;"),
+        "E0017"
+    );
+}
+
+#[test]
+fn repl_lines_have_a_budget_each() {
+    let mut repl = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("--max-time=0.2")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut input = repl.stdin.take().unwrap();
+    input.write_all(b"while (true) {}\n").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    input.write_all(b"print \"after\";\n").unwrap();
+    drop(input);
+
+    let output = repl.wait_with_output().unwrap();
+
+    assert!(stderr(&output).contains("Error[E0015]"));
+    assert!(stdout(&output).contains("after"));
+}
+
+#[test]
+fn the_command_line_allows_deeper_recursion() {
+    let output = script(
+        "limits-depth",
+        "fun f(n) { if (n > 0) f(n - 1); }\nf(1000);\nprint \"done\";",
+        &["{}"],
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "done\n");
+}
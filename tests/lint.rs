@@ -0,0 +1,139 @@
+mod common;
+
+use common::{script, stderr};
+
+/// Codes and lines of the warnings `yayarlox lint` reports for the code,
+/// and whether it succeeded
+fn lint(name: &str, code: &str) -> (Vec<(String, usize)>, bool) {
+    let output = script(name, code, &["lint", "{}"]);
+    let warnings = stderr(&output)
+        .lines()
+        .filter_map(|line| {
+            let line = line.strip_prefix("[line ")?;
+            let (number, rest) = line.split_once("] Warning[")?;
+
+            Some((String::from(&rest[..5]), number.parse().unwrap()))
+        })
+        .collect();
+
+    (warnings, output.status.success())
+}
+
+fn warning(code: &str, line: usize) -> (String, usize) {
+    (String::from(code), line)
+}
+
+#[test]
+fn clean_code_passes() {
+    let (warnings, success) = lint(
+        "lint-clean",
+        "var total = 0;\n\
+         fun add(n) { total = total + n; }\n\
+         for (var i = 0; i < 3; i = i + 1) add(i);\n\
+         for (;;) { exit(0); }\n\
+         print total;\n",
+    );
+
+    assert_eq!(warnings, []);
+    assert!(success);
+}
+
+#[test]
+fn loops_without_increment_have_no_effect_warning() {
+    let (warnings, success) = lint(
+        "lint-for",
+        "var n = 3;\n\
+         for (var i = 0; i < n;) i = i + 1;\n\
+         for (; n > 0;) { n = n - 1; }\n\
+         for (;;) { print n; exit(0); }\n",
+    );
+
+    assert_eq!(warnings, []);
+    assert!(success);
+}
+
+#[test]
+fn unused_declarations() {
+    let (warnings, success) = lint(
+        "lint-unused",
+        "fun f(used, unused, _ignored) {\n    var local = 1;\n    var _skipped;\n    print used;\n}\nf(1, 2, 3);\n",
+    );
+
+    assert_eq!(warnings, [warning("W0002", 1), warning("W0001", 2)]);
+    assert!(!success);
+}
+
+#[test]
+fn shadowing_and_redeclared_globals() {
+    let (warnings, _) = lint(
+        "lint-shadowing",
+        "var a = 1;\nvar a = 2;\n{\n    var b = a;\n    {\n        var b = 3;\n        print b;\n    }\n    print b;\n}\n",
+    );
+
+    assert_eq!(warnings, [warning("W0008", 2), warning("W0003", 6)]);
+}
+
+#[test]
+fn unreachable_code_is_reported_once() {
+    let (warnings, _) = lint(
+        "lint-unreachable",
+        "fun f() {\n    throw \"no\";\n    print 1;\n    print 2;\n}\nf();\nexit(0);\nprint 3;\n",
+    );
+
+    assert_eq!(warnings, [warning("W0004", 3), warning("W0004", 8)]);
+}
+
+#[test]
+fn expressions_without_effect() {
+    let (warnings, _) = lint(
+        "lint-no-effect",
+        "var a = 1;\na + 1;\n(a);\na = 2;\nprint a;\n",
+    );
+
+    assert_eq!(warnings, [warning("W0005", 2), warning("W0005", 3)]);
+}
+
+#[test]
+fn assignments_in_conditions() {
+    let (warnings, _) = lint(
+        "lint-assignment",
+        "var a = 1;\nif (a = 2) print a;\nif (!(a = 3) or a) print a;\nif (a == 2) print a;\n",
+    );
+
+    assert_eq!(warnings, [warning("W0006", 2), warning("W0006", 3)]);
+}
+
+#[test]
+fn calls_with_the_wrong_arity() {
+    let (warnings, _) = lint(
+        "lint-arity",
+        "fun two(a, b) { print a + b; }\ntwo(1);\ntwo(1, 2);\nlen(\"a\", \"b\");\n",
+    );
+
+    assert_eq!(warnings, [warning("W0007", 2), warning("W0007", 4)]);
+}
+
+#[test]
+fn divisions_by_zero() {
+    let (warnings, _) = lint(
+        "lint-division",
+        "print 1 / 0;\nprint 1 / -(0);\nprint 1 / 2;\n",
+    );
+
+    assert_eq!(warnings, [warning("W0010", 1), warning("W0010", 2)]);
+}
+
+#[test]
+fn levels_apply_to_lint() {
+    let output = script(
+        "lint-allowed",
+        "//! allow(no-effect)\n1;\n",
+        &["lint", "{}"],
+    );
+    assert!(output.status.success());
+    assert_eq!(stderr(&output), "");
+
+    let output = script("lint-denied", "1;\n", &["--deny=W0005", "lint", "{}"]);
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[W0005]"));
+}
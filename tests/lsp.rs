@@ -0,0 +1,128 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn frame(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Sends the messages to a server, returns the bodies of those it sent
+/// back, and its exit status
+fn session(messages: &[&str]) -> (Vec<String>, Option<i32>) {
+    raw_session(messages.iter().map(|message| frame(message)).collect())
+}
+
+/// Same as `session`, with the headers sent as they are
+fn raw_session(input: String) -> (Vec<String>, Option<i32>) {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    server
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let output = server.wait_with_output().unwrap();
+    let status = output.status.code();
+    let output = String::from_utf8(output.stdout).unwrap();
+
+    let mut bodies = Vec::new();
+    let mut rest = output.as_str();
+
+    while let Some(start) = rest.find("Content-Length: ") {
+        let headers = &rest[start + "Content-Length: ".len()..];
+        let (length, headers) = headers.split_once("\r\n\r\n").unwrap();
+        let length: usize = length.trim().parse().unwrap();
+
+        bodies.push(String::from(&headers[..length]));
+        rest = &headers[length..];
+    }
+
+    (bodies, status)
+}
+
+const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+const SHUTDOWN: &str = r#"{"jsonrpc":"2.0","id":9,"method":"shutdown"}"#;
+const EXIT: &str = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+
+fn did_open(text: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///a.lox","languageId":"lox","version":1,"text":"{}"}}}}}}"#,
+        text
+    )
+}
+
+#[test]
+fn initialize_open_and_find_definition() {
+    let open = did_open(r"var answer = 42;\nprint answer;\n");
+    let definition = r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/definition","params":{"textDocument":{"uri":"file:///a.lox"},"position":{"line":1,"character":7}}}"#;
+
+    let (responses, status) = session(&[INITIALIZE, &open, definition, SHUTDOWN, EXIT]);
+
+    assert_eq!(responses.len(), 4);
+    assert!(responses[0].contains(r#""id":1,"result":{"capabilities":"#));
+    assert!(responses[0].contains(r#""definitionProvider":true"#));
+
+    assert!(responses[1].contains(r#""method":"textDocument/publishDiagnostics""#));
+    assert!(responses[1].contains(r#""diagnostics":[]"#));
+
+    assert_eq!(
+        responses[2],
+        r#"{"jsonrpc":"2.0","id":2,"result":{"uri":"file:///a.lox","range":{"start":{"line":0,"character":4},"end":{"line":0,"character":10}}}}"#
+    );
+    assert_eq!(responses[3], r#"{"jsonrpc":"2.0","id":9,"result":null}"#);
+    assert_eq!(status, Some(0));
+}
+
+#[test]
+fn resolver_errors_are_published() {
+    let open = did_open(r"{ var a = 1; var a = 2; }\n");
+
+    let (responses, _) = session(&[INITIALIZE, &open, SHUTDOWN, EXIT]);
+
+    assert!(responses[1].contains(r#""severity":1,"code":"R0001""#));
+    assert!(responses[1]
+        .contains(r#""message":"Error at 'a': Already a variable with this name in this scope.""#));
+}
+
+#[test]
+fn unknown_requests_are_answered() {
+    let request = r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/rename","params":{}}"#;
+
+    let (responses, _) = session(&[INITIALIZE, request, SHUTDOWN, EXIT]);
+
+    assert!(responses[1].contains(r#""id":2,"error":{"code":-32601"#));
+}
+
+#[test]
+fn exiting_without_shutdown_fails() {
+    let (_, status) = session(&[INITIALIZE, EXIT]);
+
+    assert_eq!(status, Some(1));
+}
+
+#[test]
+fn oversized_messages_are_refused() {
+    // far too large to be allocated
+    let (bodies, status) = raw_session(String::from("Content-Length: 999999999999999\r\n\r\n{}"));
+
+    assert_eq!(status, Some(1));
+    assert_eq!(bodies.len(), 1);
+    assert!(bodies[0].contains("-32700"), "{}", bodies[0]);
+    assert!(bodies[0].contains("over the limit"), "{}", bodies[0]);
+
+    // skipped whole, the messages after it are still read
+    let large = " ".repeat(9 * 1024 * 1024);
+    let input = frame(&large) + &frame(INITIALIZE) + &frame(SHUTDOWN) + &frame(EXIT);
+    let (bodies, status) = raw_session(input);
+
+    assert_eq!(status, Some(0));
+    assert_eq!(bodies.len(), 3);
+    assert!(bodies[0].contains("-32700"));
+    assert!(bodies[1].contains("\"capabilities\""));
+}
@@ -0,0 +1,276 @@
+mod common;
+
+use common::{error_code, error_message, exec, script, stderr, stdout, string, value, TempDir};
+use std::fs;
+use yayarlox::capabilities::Capability;
+use yayarlox::{types, Interpreter};
+
+/// A directory holding `data/inside.txt`, `data2/outside.txt` and `secret.txt`
+fn files(name: &str) -> TempDir {
+    let directory = TempDir::new(name);
+
+    fs::create_dir(directory.path().join("data")).unwrap();
+    fs::create_dir(directory.path().join("data2")).unwrap();
+    fs::write(directory.path().join("data/inside.txt"), "inside").unwrap();
+    fs::write(directory.path().join("data2/outside.txt"), "outside").unwrap();
+    fs::write(directory.path().join("secret.txt"), "secret").unwrap();
+
+    directory
+}
+
+#[test]
+fn natives_need_their_capability() {
+    let mut interpreter = Interpreter::builder().build();
+
+    for capability in Capability::ALL.iter() {
+        for native in capability.natives() {
+            // every native is still defined, as a function
+            assert_eq!(
+                value(&mut interpreter, &format!("type({})", native)),
+                string("function")
+            );
+        }
+    }
+
+    for code in [
+        "readFile(\"x\");",
+        "fileExists(\"x\");",
+        "listDir(\".\");",
+        "writeFile(\"x\", \"\");",
+        "appendFile(\"x\", \"\");",
+        "args();",
+        "getenv(\"HOME\");",
+        "exit(1);",
+        "readLine();",
+        "clock();",
+        "random();",
+        "seed(1);",
+    ]
+    .iter()
+    {
+        assert_eq!(error_code(&mut interpreter, code), "E0018", "{}", code);
+    }
+
+    assert_eq!(
+        error_message(&mut interpreter, "getenv(\"HOME\");"),
+        "Permission denied: 'getenv' needs the env capability."
+    );
+}
+
+#[test]
+fn denials_can_be_caught() {
+    let mut interpreter = Interpreter::builder().build();
+
+    exec(
+        &mut interpreter,
+        "var denied = false;\ntry { clock(); } catch (e) { denied = true; }",
+    );
+
+    assert_eq!(value(&mut interpreter, "denied"), types::boolean(true));
+}
+
+#[test]
+fn capabilities_are_allowed_one_by_one() {
+    let mut interpreter = Interpreter::builder().allow(Capability::Env).build();
+
+    assert_eq!(value(&mut interpreter, "type(args())"), string("list"));
+    assert_eq!(error_code(&mut interpreter, "random();"), "E0018");
+}
+
+#[test]
+fn reading_is_restricted_to_directories() {
+    let directory = files("permissions-read");
+    let mut interpreter = Interpreter::builder()
+        .allow_read(directory.path().join("data"))
+        .build();
+
+    assert_eq!(
+        value(
+            &mut interpreter,
+            &format!("readFile({})", directory.literal("data/inside.txt"))
+        ),
+        string("inside")
+    );
+
+    for path in [
+        "secret.txt",
+        // a sibling sharing the directory's name as a prefix
+        "data2/outside.txt",
+        "data/../secret.txt",
+        "data/missing/../../secret.txt",
+    ]
+    .iter()
+    {
+        let code = format!("readFile({});", directory.literal(path));
+        assert_eq!(error_code(&mut interpreter, &code), "E0018", "{}", path);
+    }
+
+    // without telling whether a file exists
+    let code = format!("fileExists({});", directory.literal("secret.txt"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0018");
+
+    assert!(error_message(
+        &mut interpreter,
+        &format!("readFile({});", directory.literal("data/../secret.txt"))
+    )
+    .ends_with(&format!(
+        "'{}' isn't allowed outside of the directories given.",
+        directory.path().join("secret.txt").display()
+    )));
+
+    // reading doesn't allow writing
+    let code = format!("writeFile({}, \"\");", directory.literal("data/new.txt"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0018");
+}
+
+#[test]
+fn writing_is_restricted_to_directories() {
+    let directory = files("permissions-write");
+    let mut interpreter = Interpreter::builder()
+        .allow_write(directory.path().join("data"))
+        .build();
+
+    exec(
+        &mut interpreter,
+        &format!("writeFile({}, \"new\");", directory.literal("data/new.txt")),
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("data/new.txt")).unwrap(),
+        "new"
+    );
+
+    for path in [
+        "secret.txt",
+        "new.txt",
+        "data/../new.txt",
+        "data/missing/../../new.txt",
+    ]
+    .iter()
+    {
+        let code = format!("appendFile({}, \"x\");", directory.literal(path));
+        assert_eq!(error_code(&mut interpreter, &code), "E0018", "{}", path);
+    }
+
+    assert_eq!(
+        fs::read_to_string(directory.path().join("secret.txt")).unwrap(),
+        "secret"
+    );
+    assert!(!directory.path().join("new.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_links_dont_escape() {
+    let directory = files("permissions-links");
+    std::os::unix::fs::symlink(
+        directory.path().join("secret.txt"),
+        directory.path().join("data/link.txt"),
+    )
+    .unwrap();
+
+    let mut interpreter = Interpreter::builder()
+        .allow_read(directory.path().join("data"))
+        .allow_write(directory.path().join("data"))
+        .build();
+
+    let code = format!("readFile({});", directory.literal("data/link.txt"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0018");
+
+    let code = format!("writeFile({}, \"\");", directory.literal("data/link.txt"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0018");
+    assert_eq!(
+        fs::read_to_string(directory.path().join("secret.txt")).unwrap(),
+        "secret"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn links_to_missing_files_dont_escape() {
+    let directory = files("permissions-dangling");
+    std::os::unix::fs::symlink(
+        directory.path().join("created.txt"),
+        directory.path().join("data/link.txt"),
+    )
+    .unwrap();
+
+    let mut interpreter = Interpreter::builder()
+        .allow_read(directory.path().join("data"))
+        .allow_write(directory.path().join("data"))
+        .build();
+
+    for native in ["writeFile", "appendFile"].iter() {
+        let code = format!("{}({}, \"x\");", native, directory.literal("data/link.txt"));
+        assert_eq!(error_code(&mut interpreter, &code), "E0018", "{}", native);
+    }
+    assert!(!directory.path().join("created.txt").exists());
+
+    let code = format!("fileExists({});", directory.literal("data/link.txt"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0018");
+}
+
+#[test]
+fn imports_are_reads() {
+    let directory = files("permissions-imports");
+    fs::write(directory.path().join("lib.lox"), "var loaded = true;").unwrap();
+    fs::write(
+        directory.path().join("data/main.lox"),
+        "import \"../lib.lox\";",
+    )
+    .unwrap();
+
+    let mut interpreter = Interpreter::builder()
+        .allow_read(directory.path().join("data"))
+        .build();
+
+    let code = format!("import {};", directory.literal("data/main.lox"));
+    assert_eq!(error_code(&mut interpreter, &code), "E0018");
+}
+
+#[test]
+fn command_line_sandbox() {
+    let output = script(
+        "permissions-sandbox",
+        "print getenv(\"HOME\");",
+        &["--sandbox", "{}"],
+    );
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[E0018]"));
+
+    let output = script(
+        "permissions-allowed",
+        "print getenv(\"YAYARLOX_UNSET\") == nil;",
+        &["--sandbox", "--allow-env", "{}"],
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "true\n");
+}
+
+#[test]
+fn command_line_directories() {
+    let directory = files("permissions-flags");
+    let data = format!("--allow-read={}", directory.path().join("data").display());
+    let code = format!(
+        "print readFile({});\nprint readFile({});",
+        directory.literal("data/inside.txt"),
+        directory.literal("data/../secret.txt")
+    );
+
+    let output = script("permissions-flags-script", &code, &[&data, "{}"]);
+
+    assert_eq!(stdout(&output), "inside\n");
+    assert!(stderr(&output).contains("Error[E0018]"));
+    assert!(!stdout(&output).contains("secret"));
+
+    // allowing a capability sandboxes the script
+    let output = script(
+        "permissions-flags-env",
+        "print getenv(\"YAYARLOX_UNSET\");\nprint clock() > 0;",
+        &["--allow-env", "{}"],
+    );
+
+    assert_eq!(stdout(&output), "nil\n");
+    assert!(stderr(&output).contains("'clock' needs the clock capability"));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{stderr, stdout, TempDir};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const PROGRAM: &str = r#"
+var a = (1 + 2) * -3;
+var b;
+print !(a < 2) and b == nil or "s";
+fun add(x, y) {
+    print x + y;
+}
+add(1, 2.5);
+if (a > 1) print "big"; else if (a < -10) print "small"; else {}
+while ((a < 0)) a = a + 1;
+for (var i = 0; i < 3; i = i + 1) print i;
+for (;;) {
+    print "once";
+}
+for (a = 1; a < 2;) a = a + 1;
+{
+    var c = a;
+}
+try {
+    throw error("e");
+} catch (e) {
+    print e;
+} finally {
+    print "done";
+}
+try {} finally {}
+test "adds" {
+    assertEqual(1 + 1, 2);
+}
+"#;
+
+fn ast(directory: &Path, name: &str, code: &str, lox: bool) -> String {
+    let path = directory.join(name);
+    fs::write(&path, code).unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_yayarlox"));
+    command.arg("ast");
+    if lox {
+        command.arg("--lox");
+    }
+
+    let output = command.arg(&path).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    stdout(&output)
+}
+
+#[test]
+fn lox_output_parses_back_to_the_same_tree() {
+    let directory = TempDir::new("printer-round-trip");
+
+    let tree = ast(directory.path(), "original.lox", PROGRAM, false);
+    let printed = ast(directory.path(), "original.lox", PROGRAM, true);
+
+    assert_eq!(ast(directory.path(), "printed.lox", &printed, false), tree);
+    assert_eq!(
+        ast(directory.path(), "printed.lox", &printed, true),
+        printed
+    );
+}
+
+#[test]
+fn conditions_keep_their_own_parentheses() {
+    let directory = TempDir::new("printer-conditions");
+    let code = "while (a) a = false;\nif ((a)) print a;\nfor (; a < 1; a = 1) print a;\n";
+
+    assert_eq!(
+        ast(directory.path(), "conditions.lox", code, false),
+        "(while a (expr (= a false)))\n\
+         (if (group a) (print a) (block))\n\
+         (block (block) (while (< a 1) (block (print a) (expr (= a 1)))))\n"
+    );
+    assert_eq!(
+        ast(directory.path(), "conditions.lox", code, true),
+        "while (a)\n    a = false;\n\
+         if ((a))\n    print a;\n\
+         {\n    {}\n    while (a < 1) {\n        print a;\n        a = 1;\n    }\n}\n"
+    );
+}
+
+#[test]
+fn s_expressions_make_precedence_explicit() {
+    let directory = TempDir::new("printer-sexpr");
+
+    assert_eq!(
+        ast(
+            directory.path(),
+            "sexpr.lox",
+            "print -123 * (45.67);",
+            false
+        ),
+        "(print (* (- 123) (group 45.67)))\n"
+    );
+}
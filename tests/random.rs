@@ -0,0 +1,92 @@
+mod common;
+
+use common::{error_code, exec, script, stdout, value};
+use yayarlox::capabilities::Capability;
+use yayarlox::{types, Interpreter};
+
+const DRAWS: &str = r#"
+var draws = "";
+for (var i = 0; i < 20; i = i + 1) {
+    draws = draws + str(random()) + " " + str(randomInt(1, 6)) + ";";
+}
+"#;
+
+/// The values drawn by a new interpreter after `setup`
+fn draws(setup: &str) -> types {
+    let mut interpreter = Interpreter::builder().allow(Capability::Random).build();
+
+    exec(&mut interpreter, setup);
+    exec(&mut interpreter, DRAWS);
+
+    value(&mut interpreter, "draws")
+}
+
+#[test]
+fn seeded_draws_are_reproducible() {
+    assert_eq!(draws("seed(42);"), draws("seed(42);"));
+    assert_ne!(draws("seed(42);"), draws("seed(43);"));
+}
+
+#[test]
+fn seeding_again_restarts_the_sequence() {
+    let mut interpreter = Interpreter::builder().allow(Capability::Random).build();
+
+    exec(&mut interpreter, "seed(7); var first = random();");
+    exec(&mut interpreter, "random(); seed(7); var again = random();");
+
+    assert_eq!(
+        value(&mut interpreter, "first"),
+        value(&mut interpreter, "again")
+    );
+}
+
+#[test]
+fn draws_stay_in_range() {
+    let mut interpreter = Interpreter::builder().allow(Capability::Random).build();
+
+    exec(
+        &mut interpreter,
+        r#"
+seed(1);
+var ok = true;
+for (var i = 0; i < 1000; i = i + 1) {
+    var x = random();
+    var n = randomInt(-2, 2);
+    ok = ok and x >= 0 and x < 1 and n >= -2 and n <= 2 and n == floor(n);
+}
+"#,
+    );
+
+    assert_eq!(value(&mut interpreter, "ok"), types::boolean(true));
+    assert_eq!(error_code(&mut interpreter, "randomInt(3, 1);"), "E0005");
+}
+
+#[test]
+fn seed_option_of_the_command_line() {
+    let code = "print random(); print randomInt(1, 100);";
+
+    let first = script("random-seed", code, &["--seed=5", "{}"]);
+    let second = script("random-seed-again", code, &["--seed=5", "{}"]);
+
+    assert!(first.status.success());
+    assert_eq!(stdout(&first), stdout(&second));
+}
+
+#[test]
+fn math() {
+    let mut interpreter = Interpreter::builder().build();
+
+    assert_eq!(value(&mut interpreter, "sqrt(16)"), types::number(4.0));
+    assert_eq!(value(&mut interpreter, "pow(2, 10)"), types::number(1024.0));
+    assert_eq!(value(&mut interpreter, "min(3, -1)"), types::number(-1.0));
+    assert_eq!(value(&mut interpreter, "round(2.5)"), types::number(3.0));
+    assert_eq!(
+        value(&mut interpreter, "PI"),
+        types::number(std::f64::consts::PI)
+    );
+    assert_eq!(
+        value(&mut interpreter, "INF > 1000000"),
+        types::boolean(true)
+    );
+    assert_eq!(value(&mut interpreter, "NAN == NAN"), types::boolean(false));
+}
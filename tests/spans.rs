@@ -0,0 +1,107 @@
+mod common;
+
+use common::{run, script, stderr};
+use yayarlox::{errors, Interpreter, Interrupt};
+
+/// What the command line prints lexing or parsing the code, as the source
+/// of code which doesn't parse is only kept while it's being reported
+fn parse_error(code: &str) -> String {
+    let output = script("spans", code, &["{}"]);
+
+    assert_eq!(output.status.code(), Some(65), "{} parsed", code);
+    stderr(&output)
+}
+
+/// The error running the code fails with, as printed
+fn runtime_error(code: &str) -> String {
+    match run(&mut Interpreter::builder().build(), code) {
+        Err(Interrupt::Error(error)) => errors::render(&error.diagnostic),
+        _ => panic!("{} didn't fail", code),
+    }
+}
+
+#[test]
+fn lexing_errors_underline_the_token() {
+    let printed = parse_error("var a = 1;\nprint \"unterminated\n");
+
+    assert!(printed.starts_with("[line 2] Error[L0003]: Unterminated string.\n"));
+    assert!(printed.contains("script.lox:2:7\n"), "{}", printed);
+    assert!(
+        printed.contains("2 | print \"unterminated\n  |       ^^^^^^^^^^^^^\n"),
+        "{}",
+        printed
+    );
+}
+
+#[test]
+fn parsing_errors_underline_the_unexpected_token() {
+    let printed = parse_error("print 1 +;");
+
+    assert!(printed.starts_with("[line 1] Error[P0001] at ';': Expect expression.\n"));
+    assert!(printed.contains("script.lox:1:10\n"), "{}", printed);
+    assert!(
+        printed.contains("1 | print 1 +;\n  |          ^\n"),
+        "{}",
+        printed
+    );
+}
+
+#[test]
+fn operands_are_labelled_with_their_type() {
+    assert_eq!(
+        runtime_error("print 1 +\n  nil;"),
+        "[line 1] Error[E0004] at '+': Operands must be two numbers or two strings\n  \
+         --> <test>:1:9\n  \
+         |\n\
+         1 | print 1 +\n  \
+         |         ^\n  \
+         |\n\
+         1 | print 1 +\n  \
+         |       - number\n  \
+         |\n\
+         2 |   nil;\n  \
+         |   --- nil\n"
+    );
+}
+
+#[test]
+fn columns_count_characters_and_keep_tabs() {
+    let rendered = runtime_error("print \"été\" + nil;");
+    assert!(rendered.contains("--> <test>:1:13\n"), "{}", rendered);
+    assert!(rendered.contains("1 | print \"été\" + nil;\n  |             ^\n"));
+    assert!(rendered.contains("  |       ----- string\n"));
+
+    // tabs are kept so that carets stay under the code, whatever their width
+    let rendered = runtime_error("\tprint 1 - \"a\";");
+    assert!(rendered.contains("--> <test>:1:10\n"), "{}", rendered);
+    assert!(rendered.contains("1 | \tprint 1 - \"a\";\n  | \t        ^\n"));
+}
+
+#[test]
+fn calls_underline_their_closing_parenthesis() {
+    let rendered = runtime_error("fun f(a) {}\nf(1,\n  2);");
+
+    assert!(rendered.starts_with(
+        "[line 3] Error[E0003] at ')': Expected 1 arguments but got 2.\n  --> <test>:3:4\n"
+    ));
+    assert!(rendered.contains("3 |   2);\n  |    ^\n"));
+}
+
+#[test]
+fn statements_report_their_first_error_only() {
+    let printed = parse_error("print ;\nprint 1 +;\n{ print (1; print 2; }\nvar = 1;\nprint 1;\n");
+    let errors: Vec<_> = printed
+        .lines()
+        .filter(|line| line.starts_with("[line "))
+        .collect();
+
+    assert_eq!(
+        errors,
+        [
+            "[line 1] Error[P0001] at ';': Expect expression.",
+            "[line 2] Error[P0001] at ';': Expect expression.",
+            "[line 3] Error[P0003] at '1': Expected ')' after expression.",
+            "[line 4] Error[P0004] at 'var': Expect variable name.",
+        ]
+    );
+}
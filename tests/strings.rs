@@ -0,0 +1,77 @@
+mod common;
+
+use common::{error_code, string, value};
+use yayarlox::{types, Interpreter};
+
+fn eval(expression: &str) -> types {
+    value(&mut Interpreter::builder().build(), expression)
+}
+
+#[test]
+fn positions_count_characters() {
+    assert_eq!(eval("len(\"héllo\")"), types::number(5.0));
+    assert_eq!(eval("len(\"😀\")"), types::number(1.0));
+    assert_eq!(eval("substring(\"日本語です\", 1, 3)"), string("本語"));
+    assert_eq!(eval("indexOf(\"日本語\", \"語\")"), types::number(2.0));
+    assert_eq!(eval("indexOf(\"日本語\", \"x\")"), types::number(-1.0));
+}
+
+#[test]
+fn searching() {
+    assert_eq!(eval("contains(\"naïve\", \"ï\")"), types::boolean(true));
+    assert_eq!(eval("startsWith(\"ünï\", \"ü\")"), types::boolean(true));
+    assert_eq!(eval("endsWith(\"ünï\", \"ï\")"), types::boolean(true));
+    assert_eq!(eval("endsWith(\"ünï\", \"i\")"), types::boolean(false));
+}
+
+#[test]
+fn case_and_whitespace() {
+    assert_eq!(eval("upper(\"straße\")"), string("STRASSE"));
+    assert_eq!(eval("lower(\"ÉTÉ\")"), string("été"));
+    assert_eq!(eval("trim(\" \t á \")"), string("á"));
+}
+
+#[test]
+fn splitting_joining_and_replacing() {
+    assert_eq!(
+        eval("split(\"a,é,b\", \",\")"),
+        types::list(vec![string("a"), string("é"), string("b")])
+    );
+    assert_eq!(eval("join(split(\"x,ÿ\", \",\"), \"→\")"), string("x→ÿ"));
+    assert_eq!(eval("replace(\"ééé\", \"é\", \"e\")"), string("eee"));
+    assert_eq!(eval("repeat(\"ñ\", 3)"), string("ñññ"));
+}
+
+#[test]
+fn character_codes() {
+    assert_eq!(eval("charCode(\"é\")"), types::number(233.0));
+    assert_eq!(eval("charCode(\"😀\")"), types::number(128_512.0));
+    assert_eq!(eval("fromCharCode(128512)"), string("😀"));
+}
+
+#[test]
+fn comparison() {
+    assert_eq!(eval("\"é\" > \"e\""), types::boolean(true));
+    assert_eq!(eval("\"abc\" < \"abd\""), types::boolean(true));
+}
+
+#[test]
+fn bad_arguments_are_errors() {
+    let mut interpreter = Interpreter::builder().build();
+
+    for code in [
+        "substring(\"日本\", 1, 3);",
+        "substring(\"abc\", 2, 1);",
+        "charCode(\"ab\");",
+        "fromCharCode(55296);",
+        "replace(\"abc\", \"\", \"x\");",
+        "join(\"abc\", \",\");",
+        "len(1);",
+        "repeat(\"x\", 4000000000);",
+        "repeat(repeat(\"x\", 1000), 4000000);",
+    ]
+    .iter()
+    {
+        assert_eq!(error_code(&mut interpreter, code), "E0005", "{}", code);
+    }
+}
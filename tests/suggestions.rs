@@ -0,0 +1,77 @@
+mod common;
+
+use common::{error_message, exec, script, stderr};
+use yayarlox::Interpreter;
+
+#[test]
+fn close_names_are_suggested_for_undefined_variables() {
+    let mut interpreter = Interpreter::builder().build();
+    exec(&mut interpreter, "var counter = 0;\nfun increment() {}");
+
+    assert_eq!(
+        error_message(&mut interpreter, "print countr;"),
+        "Variable 'countr' doesn't exist, did you mean 'counter'?"
+    );
+    assert_eq!(
+        error_message(&mut interpreter, "incremnet();"),
+        "Variable 'incremnet' doesn't exist, did you mean 'increment'?"
+    );
+    // natives too
+    assert_eq!(
+        error_message(&mut interpreter, "print lne(\"a\");"),
+        "Variable 'lne' doesn't exist, did you mean 'len'?"
+    );
+    // nothing close enough
+    assert_eq!(
+        error_message(&mut interpreter, "print zzz;"),
+        "Variable 'zzz' doesn't exist."
+    );
+}
+
+#[test]
+fn inner_scopes_are_preferred() {
+    let mut interpreter = Interpreter::builder().build();
+
+    assert_eq!(
+        error_message(
+            &mut interpreter,
+            "var valeus = 1;\n{\n    var value = 2;\n    print valeu;\n}"
+        ),
+        "Variable 'valeu' doesn't exist, did you mean 'value'?"
+    );
+}
+
+#[test]
+fn misspelled_keywords_are_suggested_by_the_parser() {
+    for (code, keyword) in [
+        ("whille (true) { print 1; }", "while"),
+        ("fro (var i = 0; i < 1; i = i + 1) {}", "for"),
+        ("pirnt \"hi\";", "print"),
+        ("vra x = 1;", "var"),
+        ("fnu f() {}", "fun"),
+        ("thorw \"e\";", "throw"),
+    ]
+    .iter()
+    {
+        let output = script("suggestions-keywords", code, &["{}"]);
+        let printed = stderr(&output);
+
+        assert_eq!(output.status.code(), Some(65), "{}", code);
+        assert!(printed.contains("Error[P0002]"), "{}", printed);
+        assert!(
+            printed.contains(&format!("did you mean '{}'?", keyword)),
+            "{}",
+            printed
+        );
+    }
+}
+
+#[test]
+fn names_unlike_keywords_get_no_suggestion() {
+    for code in ["foo bar;", "fi (true) print 1;", "whale x;"].iter() {
+        let output = script("suggestions-none", code, &["{}"]);
+
+        assert_eq!(output.status.code(), Some(65), "{}", code);
+        assert!(!stderr(&output).contains("did you mean"), "{}", code);
+    }
+}
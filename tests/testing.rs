@@ -0,0 +1,215 @@
+mod common;
+
+use common::{stderr, stdout, TempDir};
+use std::fs;
+use std::process::{Command, Output};
+
+fn test(paths: &[&std::path::Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .arg("test")
+        .args(paths)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn tests_are_isolated() {
+    let directory = TempDir::new("testing-isolated");
+    let file = directory.path().join("counter.lox");
+    fs::write(
+        &file,
+        r#"var count = 0;
+print "setup";
+
+test "first" {
+    count = count + 1;
+    assertEqual(count, 1);
+}
+
+fun test_second() {
+    count = count + 1;
+    assertEqual(count, 1);
+}
+
+test "third" {
+    assertEqual(count, 0);
+}
+"#,
+    )
+    .unwrap();
+
+    let output = test(&[&file]);
+    let name = file.display();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "setup\n{0} > first ... ok\nsetup\n{0} > test_second ... ok\n\
+             setup\n{0} > third ... ok\n\n3 passed, 0 failed\n",
+            name
+        )
+    );
+}
+
+#[test]
+fn failures_dont_stop_the_others() {
+    let directory = TempDir::new("testing-failures");
+    let file = directory.path().join("failing.lox");
+    fs::write(
+        &file,
+        r#"test "assertion" {
+    assert(1 > 2, "one isn't more");
+}
+
+test "uncaught" {
+    nil();
+}
+
+test "exit" {
+    exit(3);
+}
+
+test "passing" {
+    assertEqual(1 + 1, 2);
+}
+"#,
+    )
+    .unwrap();
+
+    let output = test(&[&file]);
+    let name = file.display();
+    let out = stdout(&output);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(out.contains(&format!("{} > assertion ... FAILED", name)));
+    assert!(out.contains(&format!("{} > passing ... ok", name)));
+    assert!(out.ends_with(&format!(
+        "Failures:\n    {0} > assertion [line 2]\n    {0} > uncaught [line 6]\n    \
+         {0} > exit [line 9]\n\n1 passed, 3 failed\n",
+        name
+    )));
+
+    let errors = stderr(&output);
+    assert!(errors.contains("Error[E0013]"));
+    assert!(errors.contains("one isn't more"));
+    assert!(errors.contains("Error[E0002]"));
+    assert!(errors.contains("Test exited with status 3."));
+}
+
+#[test]
+fn directories_are_searched() {
+    let directory = TempDir::new("testing-search");
+    fs::create_dir(directory.path().join("nested")).unwrap();
+    fs::write(
+        directory.path().join("nested/b.lox"),
+        "test \"b\" { assert(true, \"\"); }",
+    )
+    .unwrap();
+    fs::write(
+        directory.path().join("a.lox"),
+        "test \"a\" { assert(true, \"\"); }",
+    )
+    .unwrap();
+    fs::write(directory.path().join("notes.txt"), "test \"c\" {").unwrap();
+    fs::write(directory.path().join("broken.lox"), "test \"d\" {").unwrap();
+
+    let output = test(&[directory.path()]);
+    let lines: Vec<String> = stdout(&output).lines().map(String::from).collect();
+    let path = |name: &str| directory.path().join(name).display().to_string();
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        lines[..3],
+        [
+            format!("{} > a ... ok", path("a.lox")),
+            format!("{} ... FAILED", path("broken.lox")),
+            format!("{} > b ... ok", path("nested/b.lox")),
+        ]
+    );
+    assert_eq!(lines.last().unwrap(), "2 passed, 1 failed");
+}
+
+#[cfg(unix)]
+#[test]
+fn linked_directories_arent_followed() {
+    let directory = TempDir::new("testing-links");
+    fs::write(
+        directory.path().join("a.lox"),
+        "test \"a\" { assert(true, \"\"); }",
+    )
+    .unwrap();
+    std::os::unix::fs::symlink(directory.path(), directory.path().join("loop")).unwrap();
+
+    let output = test(&[directory.path()]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).matches("> a ... ok").count(), 1);
+}
+
+#[test]
+fn levels_apply_to_tests() {
+    let directory = TempDir::new("testing-levels");
+    let file = directory.path().join("levels.lox");
+
+    fs::write(
+        &file,
+        "//! deny(type-mismatch) allow(division-by-zero)\n\
+         var half = 1 / 0;\n\
+         test \"mismatch\" { print 1 == \"1\"; }\n",
+    )
+    .unwrap();
+
+    let output = test(&[&file]);
+    let errors = stderr(&output);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stdout(&output).contains("> mismatch ... FAILED"));
+    assert!(errors.contains("Error[W0009]"), "{}", errors);
+    assert!(!errors.contains("W0010"), "{}", errors);
+
+    // denied warnings of the file fail it before its tests run
+    fs::write(&file, "var a;\nvar a;\ntest \"t\" { print a; }\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .args(["--deny=W0008", "test"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output).contains("Error[W0008]"));
+    assert!(stdout(&output).contains(&format!("{} ... FAILED", file.display())));
+}
+
+#[test]
+fn setup_counts_against_the_limits() {
+    let directory = TempDir::new("testing-limits");
+    let file = directory.path().join("steps.lox");
+
+    // each loop takes some 9000 steps
+    let code = "var i = 0;\nwhile (i < 1000) i = i + 1;\n";
+    fs::write(&file, format!("{}test \"loop\" {{ {} }}\n", code, code)).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yayarlox"))
+        .args(["--max-steps=12000", "test"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stdout(&output).contains("> loop ... FAILED"));
+    assert!(stderr(&output).contains("Error[E0014]"));
+}
+
+#[test]
+fn running_a_file_skips_its_tests() {
+    let output = common::script(
+        "testing-skipped",
+        "print \"main\";\ntest \"t\" { print \"test\"; }\n",
+        &["{}"],
+    );
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "main\n");
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{error_code, exec, string, value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use yayarlox::capabilities::Capability;
+use yayarlox::{types, Interpreter};
+
+fn interpreter() -> Interpreter {
+    Interpreter::builder().allow(Capability::Clock).build()
+}
+
+fn number(value: types) -> f64 {
+    match value {
+        types::number(number) => number,
+        other => panic!("{:?} isn't a number", other),
+    }
+}
+
+fn numbers(values: &[f64]) -> types {
+    types::list(values.iter().map(|value| types::number(*value)).collect())
+}
+
+#[test]
+fn clock_is_the_time_since_the_epoch() {
+    let before = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64();
+    let clock = number(value(&mut interpreter(), "clock()"));
+
+    assert!((clock - before).abs() < 5.0, "{} {}", clock, before);
+}
+
+#[test]
+fn now_measures_time_spent() {
+    let mut interpreter = interpreter();
+
+    exec(
+        &mut interpreter,
+        "var start = now(); sleep(0.05); var spent = now() - start;",
+    );
+
+    let spent = number(value(&mut interpreter, "spent"));
+    assert!((0.05..5.0).contains(&spent), "{}", spent);
+    assert_eq!(
+        value(&mut interpreter, "now() >= start"),
+        types::boolean(true)
+    );
+}
+
+#[test]
+fn utc_fields() {
+    let mut interpreter = interpreter();
+
+    assert_eq!(
+        value(&mut interpreter, "utc(0)"),
+        numbers(&[1970.0, 1.0, 1.0, 0.0, 0.0, 0.0, 4.0, 1.0])
+    );
+    // leap day
+    assert_eq!(
+        value(&mut interpreter, "utc(951825845)"),
+        numbers(&[2000.0, 2.0, 29.0, 12.0, 4.0, 5.0, 2.0, 60.0])
+    );
+    assert_eq!(
+        value(&mut interpreter, "utc(-1)"),
+        numbers(&[1969.0, 12.0, 31.0, 23.0, 59.0, 59.0, 3.0, 365.0])
+    );
+}
+
+#[test]
+fn formatting() {
+    let mut interpreter = interpreter();
+
+    assert_eq!(
+        value(
+            &mut interpreter,
+            "formatTime(951825845, \"%Y-%m-%d %H:%M:%S\")"
+        ),
+        string("2000-02-29 12:04:05")
+    );
+    assert_eq!(
+        value(
+            &mut interpreter,
+            "formatTime(951825845, \"%a %e %b %y, %I %p, day %j, %%\")"
+        ),
+        string("Tue 29 Feb 00, 12 PM, day 060, %")
+    );
+    assert_eq!(
+        value(&mut interpreter, "formatTime(0, \"%F %T\")"),
+        string("1970-01-01 00:00:00")
+    );
+    assert_eq!(
+        error_code(&mut interpreter, "formatTime(0, \"%Q\");"),
+        "E0005"
+    );
+}
+
+#[test]
+fn the_clock_is_a_capability() {
+    let mut interpreter = Interpreter::builder().build();
+
+    assert_eq!(error_code(&mut interpreter, "clock();"), "E0018");
+    assert_eq!(
+        value(&mut interpreter, "utc(0)"),
+        numbers(&[1970.0, 1.0, 1.0, 0.0, 0.0, 0.0, 4.0, 1.0])
+    );
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{exec, run, script, stderr, TempDir};
+use std::fs;
+use yayarlox::{Interpreter, Interrupt, RuntimeError};
+
+fn failure(interpreter: &mut Interpreter, code: &str) -> RuntimeError {
+    match run(interpreter, code) {
+        Err(Interrupt::Error(error)) => *error,
+        _ => panic!("{} didn't fail", code),
+    }
+}
+
+#[test]
+fn calls_are_listed_innermost_first() {
+    let error = failure(
+        &mut Interpreter::builder().build(),
+        "fun a(x) {\n    b(x);\n}\nfun b(x) { print x + nil; }\na(1);",
+    );
+
+    assert_eq!(
+        error.traceback(),
+        ["in b() [line 4]", "in a() [line 2]", "in script [line 5]"]
+    );
+}
+
+#[test]
+fn natives_and_thrown_values_are_traced_too() {
+    let mut interpreter = Interpreter::builder().build();
+
+    let error = failure(
+        &mut interpreter,
+        "fun parse(s) {\n    return_ = len(s);\n}\nvar return_;\nparse(1);",
+    );
+    assert_eq!(
+        error.traceback(),
+        ["in parse() [line 2]", "in script [line 5]"]
+    );
+
+    let error = failure(
+        &mut interpreter,
+        "fun check() {\n    throw \"bad\";\n}\nfun run() { check(); }\nrun();",
+    );
+    assert_eq!(
+        error.traceback(),
+        [
+            "in check() [line 2]",
+            "in run() [line 4]",
+            "in script [line 5]"
+        ]
+    );
+}
+
+#[test]
+fn long_tracebacks_keep_their_ends() {
+    let error = failure(
+        &mut Interpreter::builder().build(),
+        "fun f(n) {\n    if (n > 0) f(n - 1); else nil();\n}\nf(30);",
+    );
+    let traceback = error.traceback();
+
+    // 31 calls and the script
+    assert_eq!(traceback.len(), 21);
+    assert_eq!(traceback[0], "in f() [line 2]");
+    assert_eq!(traceback[10], "... 12 more");
+    assert_eq!(traceback[19], "in f() [line 2]");
+    assert_eq!(traceback[20], "in script [line 4]");
+}
+
+#[test]
+fn caught_errors_dont_leave_frames_behind() {
+    let mut interpreter = Interpreter::builder().build();
+
+    exec(
+        &mut interpreter,
+        "fun fail() { nil(); }\ntry { fail(); } catch (e) {}",
+    );
+
+    let error = failure(&mut interpreter, "nil();");
+    assert_eq!(error.traceback(), ["in script [line 1]"]);
+}
+
+#[test]
+fn imported_files_are_frames() {
+    let directory = TempDir::new("traceback-imports");
+    fs::write(
+        directory.path().join("lib.lox"),
+        "fun half(n) {\n    print n / \"2\";\n}\n",
+    )
+    .unwrap();
+
+    let mut interpreter = Interpreter::builder().allow_all().build();
+    let error = failure(
+        &mut interpreter,
+        &format!(
+            "import {} as lib;\nlib.half(4);",
+            directory.literal("lib.lox")
+        ),
+    );
+
+    assert_eq!(
+        error.traceback(),
+        ["in half() [line 2]", "in script [line 2]"]
+    );
+}
+
+#[test]
+fn printed_after_the_error() {
+    let output = script(
+        "traceback-cli",
+        "fun inner() { nil(); }\nfun outer() {\n    inner();\n}\nouter();\n",
+        &["{}"],
+    );
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr(&output)
+        .ends_with("  in inner() [line 1]\n  in outer() [line 3]\n  in script [line 5]\n"));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::{error_code, exec, string, value};
+use yayarlox::{types, Interpreter};
+
+fn eval(expression: &str) -> types {
+    value(&mut Interpreter::builder().build(), expression)
+}
+
+#[test]
+fn type_names() {
+    for (expression, name) in [
+        ("nil", "nil"),
+        ("true", "boolean"),
+        ("1.5", "number"),
+        ("\"s\"", "string"),
+        ("split(\"a\", \",\")", "list"),
+        ("error(\"e\")", "error"),
+        ("clock", "function"),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            eval(&format!("type({})", expression)),
+            string(name),
+            "{}",
+            expression
+        );
+    }
+
+    let mut interpreter = Interpreter::builder().build();
+    exec(&mut interpreter, "fun f() {}");
+    assert_eq!(value(&mut interpreter, "type(f)"), string("function"));
+}
+
+#[test]
+fn str_formats_as_print() {
+    assert_eq!(eval("str(1.5) + str(2) + str(nil)"), string("1.52nil"));
+    assert_eq!(eval("str(true)"), string("true"));
+    assert_eq!(eval("str(\"é\")"), string("é"));
+    assert_eq!(eval("str(split(\"a,b\", \",\"))"), string("[\"a\", \"b\"]"));
+}
+
+#[test]
+fn num_parses_or_gives_nil() {
+    assert_eq!(eval("num(\"2.5\")"), types::number(2.5));
+    assert_eq!(eval("num(\" -3 \")"), types::number(-3.0));
+    assert_eq!(eval("num(\"x\")"), types::nil);
+    assert_eq!(eval("num(\"\")"), types::nil);
+    assert_eq!(eval("num(4)"), types::number(4.0));
+}
+
+#[test]
+fn bool_is_truthiness() {
+    assert_eq!(eval("bool(0)"), types::boolean(true));
+    assert_eq!(eval("bool(\"\")"), types::boolean(true));
+    assert_eq!(eval("bool(nil)"), types::boolean(false));
+    assert_eq!(eval("bool(false)"), types::boolean(false));
+}
+
+#[test]
+fn arity_is_checked() {
+    let mut interpreter = Interpreter::builder().build();
+
+    assert_eq!(error_code(&mut interpreter, "type();"), "E0003");
+    assert_eq!(error_code(&mut interpreter, "str(1, 2);"), "E0003");
+}